@@ -0,0 +1,137 @@
+//! The operator, whitelist and ban lists, stored in the same text files as
+//! the original server.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+
+pub const OPS_FILE: &str = "ops.txt";
+pub const WHITE_LIST_FILE: &str = "white-list.txt";
+pub const BANNED_PLAYERS_FILE: &str = "banned-players.txt";
+pub const BANNED_IPS_FILE: &str = "banned-ips.txt";
+
+/// A list of player names or addresses, stored one per line.
+///
+/// Names are compared ignoring case, so they are kept in lowercase like the
+/// original does.
+#[derive(Debug, Clone)]
+pub struct NameList {
+    path: PathBuf,
+    names: BTreeSet<String>,
+}
+
+impl NameList {
+    /// Read a list from a file, creating an empty one if it doesn't exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let mut list = Self {
+            path: path.as_ref().to_path_buf(),
+            names: BTreeSet::new(),
+        };
+        list.reload()?;
+
+        Ok(list)
+    }
+
+    /// Read the list from its file again, to pick up changes made to it by
+    /// hand.
+    pub fn reload(&mut self) -> Result<(), io::Error> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                self.names.clear();
+                return self.save()
+            }
+            Err(e) => return Err(e),
+        };
+
+        self.names = contents.lines()
+            .map(|l| l.trim().to_lowercase())
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        Ok(())
+    }
+
+    fn save(&self) -> Result<(), io::Error> {
+        let mut contents = String::new();
+        for name in &self.names {
+            contents.push_str(name);
+            contents.push('\n');
+        }
+
+        fs::write(&self.path, contents)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.names.contains(&name.to_lowercase())
+    }
+
+    /// Add a name and save the list, returning `false` if it was already
+    /// there.
+    pub fn add(&mut self, name: &str) -> Result<bool, io::Error> {
+        if !self.names.insert(name.to_lowercase()) {
+            return Ok(false)
+        }
+        self.save()?;
+
+        Ok(true)
+    }
+
+    /// Remove a name and save the list, returning `false` if it wasn't
+    /// there.
+    pub fn remove(&mut self, name: &str) -> Result<bool, io::Error> {
+        if !self.names.remove(&name.to_lowercase()) {
+            return Ok(false)
+        }
+        self.save()?;
+
+        Ok(true)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+}
+
+/// Who is allowed on the server and who is an operator.
+#[derive(Debug, Clone)]
+pub struct AccessLists {
+    pub ops: NameList,
+    pub white_list: NameList,
+    pub banned_players: NameList,
+    pub banned_ips: NameList,
+}
+
+impl AccessLists {
+    /// Read every list from the current directory.
+    pub fn load() -> Result<Self, io::Error> {
+        Ok(Self {
+            ops: NameList::load(OPS_FILE)?,
+            white_list: NameList::load(WHITE_LIST_FILE)?,
+            banned_players: NameList::load(BANNED_PLAYERS_FILE)?,
+            banned_ips: NameList::load(BANNED_IPS_FILE)?,
+        })
+    }
+
+    /// Check whether a player may join, giving the reason they are kicked
+    /// with if not.
+    ///
+    /// Operators can always join when the whitelist is on.
+    pub fn check_login(&self, username: &str, address: IpAddr, white_list: bool) -> Result<(), &'static str> {
+        if self.banned_players.contains(username) {
+            return Err("You are banned from this server!")
+        }
+        if self.banned_ips.contains(&address.to_string()) {
+            return Err("Your IP address is banned from this server!")
+        }
+        if white_list && !self.white_list.contains(username) && !self.ops.contains(username) {
+            return Err("You are not white-listed on this server!")
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use std::{io, time::Duration};
+
+use ureq::Agent;
+
+/// How long to wait for the session server before giving up on a login.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Asks a session server whether players really logged in to their
+/// accounts, for servers in online mode.
+///
+/// Before logging in, the client tells the session server that it is joining
+/// a server with the hash the server sent in its handshake. The server then
+/// checks that with a request like `checkserver.jsp?user=<name>&serverId=<hash>`,
+/// which is answered with `YES` if the player is who they say they are.
+#[derive(Debug, Clone)]
+pub struct SessionServer {
+    url: String,
+    agent: Agent,
+}
+
+impl SessionServer {
+    pub fn new(url: &str) -> Self {
+        let agent = Agent::config_builder()
+            .timeout_global(Some(TIMEOUT))
+            .build()
+            .into();
+
+        Self {
+            url: url.to_string(),
+            agent,
+        }
+    }
+
+    /// Whether the session server says the player joined with the hash.
+    pub fn has_joined(&self, username: &str, server_hash: &str) -> Result<bool, io::Error> {
+        let reply = self.agent.get(&self.url)
+            .query("user", username)
+            .query("serverId", server_hash)
+            .call()
+            .and_then(|mut r| r.body_mut().read_to_string())
+            .map_err(ureq::Error::into_io)?;
+
+        Ok(reply.trim() == "YES")
+    }
+}
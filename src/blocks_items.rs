@@ -1,6 +1,7 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
-use std::fmt::Debug;
+use std::{fmt::Debug, io::{self, Read, Write}};
 
 /// A trait to unify [`Block`]s and [`Item`]s.
 pub trait BlockItemID: Debug + Clone + PartialEq {
@@ -23,23 +24,32 @@ impl BlockItemID for BlockItem {
         match self {
             Self::Unknown => -1,
             BlockItem::Block(b) => *b as i16,
-            BlockItem::Item(i) => *i as i16 + 255,
+            BlockItem::Item(i) => *i as i16 + 256,
         }
     }
 
     fn from_id(id: i16) -> Self {
-        if id <= 255 {
+        if id < 256 {
             if let Some(b) = Block::from_i16(id) {
                 Self::Block(b)
             } else {
                 Self::Unknown
             }
+        } else if let Some(b) = Item::from_i16(id - 256) {
+            Self::Item(b)
         } else {
-            if let Some(b) = Item::from_i16(id - 255) {
-                Self::Item(b)
-            } else {
-                Self::Unknown
-            }
+            Self::Unknown
+        }
+    }
+}
+
+impl BlockItem {
+    /// The most of this Block/Item which fit in one inventory slot.
+    pub fn max_stack_size(&self) -> i32 {
+        match self {
+            Self::Unknown => 0,
+            Self::Block(_) => 64,
+            Self::Item(i) => i.max_stack_size(),
         }
     }
 }
@@ -47,6 +57,7 @@ impl BlockItemID for BlockItem {
 #[repr(i16)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[derive(FromPrimitive)]
+#[allow(clippy::enum_variant_names)]
 pub enum Block {
     Air = 0,
     Stone = 1,
@@ -127,6 +138,109 @@ pub enum Block {
     PumpkinLantern = 91,
 }
 
+impl Block {
+    /// Whether a placed block can take the place of this one, like air or
+    /// liquids.
+    pub fn is_replaceable(&self) -> bool {
+        matches!(
+            self,
+            Block::Air
+                | Block::WaterStill
+                | Block::WaterMoving
+                | Block::LavaStill
+                | Block::LavaMoving
+                | Block::Fire
+        )
+    }
+
+    /// Whether a player can dig the block out. Bedrock and portals can't be
+    /// broken, and there is nothing to break in air or liquids.
+    pub fn is_breakable(&self) -> bool {
+        !self.is_liquid() && !matches!(self, Block::Air | Block::Bedrock | Block::Portal)
+    }
+
+    pub fn is_liquid(&self) -> bool {
+        matches!(
+            self,
+            Block::WaterStill | Block::WaterMoving | Block::LavaStill | Block::LavaMoving
+        )
+    }
+
+    /// Whether the block can be stood on, unlike liquids and blocks which
+    /// can be walked through like flowers or torches.
+    pub fn is_solid(&self) -> bool {
+        !self.is_liquid() && !matches!(
+            self,
+            Block::Air
+                | Block::Fire
+                | Block::Sapling
+                | Block::PlantYellow
+                | Block::PlantRed
+                | Block::MushroomBrown
+                | Block::MushroomRed
+                | Block::TorchWood
+                | Block::RedstoneWire
+                | Block::Crops
+                | Block::SignPost
+                | Block::DoorWood
+                | Block::Ladder
+                | Block::MinecartTrack
+                | Block::SignWall
+                | Block::Lever
+                | Block::PressurePlateStone
+                | Block::DoorSteel
+                | Block::PressurePlatePlanks
+                | Block::TorchRedstoneIdle
+                | Block::TorchRedstoneActive
+                | Block::Button
+                | Block::Snow
+                | Block::Reed
+                | Block::Portal
+        )
+    }
+
+    /// How much light is lost passing through the block, from 0 for clear
+    /// blocks to 15 for solid ones.
+    pub fn light_opacity(&self) -> u8 {
+        match self {
+            Block::Leaves => 1,
+            Block::WaterStill | Block::WaterMoving | Block::BlockIce => 3,
+            Block::Air
+            | Block::Sapling
+            | Block::Glass
+            | Block::PlantYellow
+            | Block::PlantRed
+            | Block::MushroomBrown
+            | Block::MushroomRed
+            | Block::TorchWood
+            | Block::Fire
+            | Block::MobSpawner
+            | Block::StairCompactPlanks
+            | Block::StairCompactCobblestone
+            | Block::RedstoneWire
+            | Block::Crops
+            | Block::SignPost
+            | Block::DoorWood
+            | Block::Ladder
+            | Block::MinecartTrack
+            | Block::SignWall
+            | Block::Lever
+            | Block::PressurePlateStone
+            | Block::DoorSteel
+            | Block::PressurePlatePlanks
+            | Block::TorchRedstoneIdle
+            | Block::TorchRedstoneActive
+            | Block::Button
+            | Block::Snow
+            | Block::Cactus
+            | Block::Reed
+            | Block::Fence
+            | Block::Portal => 0,
+            _ => 15,
+        }
+    }
+}
+
 
 
 #[repr(i16)]
@@ -232,6 +346,36 @@ pub enum Item {
     RecordCat = 2001,
 }
 
+impl Item {
+    /// The most of this item which fit in one inventory slot. Tools, armor
+    /// and food don't stack at all.
+    pub fn max_stack_size(&self) -> i32 {
+        use Item::*;
+
+        match self {
+            Snowball | Egg => 16,
+            ShovelSteel | PickaxeSteel | AxeSteel | SwordSteel
+            | ShovelWood | PickaxeWood | AxeWood | SwordWood
+            | ShovelStone | PickaxeStone | AxeStone | SwordStone
+            | ShovelDiamond | PickaxeDiamond | AxeDiamond | SwordDiamond
+            | ShovelGold | PickaxeGold | AxeGold | SwordGold
+            | HoeWood | HoeStone | HoeSteel | HoeDiamond | HoeGold
+            | HelmetLeather | PlateLeather | LegsLeather | BootsLeather
+            | HelmetChain | PlateChain | LegsChain | BootsChain
+            | HelmetSteel | PlateSteel | LegsSteel | BootsSteel
+            | HelmetDiamond | PlateDiamond | LegsDiamond | BootsDiamond
+            | HelmetGold | PlateGold | LegsGold | BootsGold
+            | FlintAndSteel | Bow | FishingRod
+            | AppleRed | AppleGold | BowlSoup | Bread | PorkRaw | PorkCooked | FishRaw | FishCooked
+            | Sign | DoorWood | DoorSteel
+            | BucketEmpty | BucketWater | BucketLava | BucketMilk
+            | MinecartEmpty | MinecartCrate | MinecartPowered | Boat | Saddle
+            | Record13 | RecordCat => 1,
+            _ => 64,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ItemStack {
     pub stack_size: i32,
@@ -250,3 +394,50 @@ impl ItemStack {
         }
     }
 }
+
+/// Read an optional [`ItemStack`] in the format used by inventory packets.
+///
+/// An ID of `-1` means the slot is empty, otherwise the ID is followed by the
+/// stack size and the damage value.
+pub trait ReadItemStack: io::Read {
+    #[inline]
+    fn read_item_stack(&mut self) -> Result<Option<ItemStack>, io::Error> {
+        let id = self.read_i16::<BE>()?;
+        if id < 0 {
+            return Ok(None)
+        }
+
+        let stack_size = self.read_u8()?;
+        let item_damage = self.read_i16::<BE>()?;
+
+        Ok(Some(ItemStack::new(id as i32, stack_size as i32, item_damage as i32)))
+    }
+}
+
+impl<R: Read> ReadItemStack for R {}
+
+pub trait WriteItemStack: io::Write {
+    #[inline]
+    fn write_item_stack(&mut self, stack: &Option<ItemStack>) -> Result<(), io::Error> {
+        match stack {
+            Some(s) if s.item_id.id() >= 0 => {
+                self.write_i16::<BE>(s.item_id.id())?;
+                self.write_u8(s.stack_size as u8)?;
+                self.write_i16::<BE>(s.item_damage as i16)?;
+            }
+            _ => self.write_i16::<BE>(-1)?,
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write> WriteItemStack for W {}
+
+/// The size in bytes of an optional [`ItemStack`] as written by [`WriteItemStack`].
+pub fn item_stack_size(stack: &Option<ItemStack>) -> usize {
+    match stack {
+        Some(s) if s.item_id.id() >= 0 => 2 + 1 + 2,
+        _ => 2,
+    }
+}
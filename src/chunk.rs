@@ -1,11 +1,13 @@
-use byteorder::{WriteBytesExt, BE};
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
 use flate2::{Compression, write::ZlibEncoder};
+use num_traits::FromPrimitive;
 use std::io::prelude::*;
 
 use crate::blocks_items::Block;
-use crate::byte_ops::ToBytes;
+use crate::packets::Packet;
 
-#[derive(Debug, Clone)]
+/// A region of blocks sent to the client, usually one whole chunk.
+#[derive(Debug, Clone, PartialEq)]
 pub struct MapChunk {
     chunk_x: i32,
     chunk_y: i16,
@@ -13,11 +15,11 @@ pub struct MapChunk {
     size_x: u8,
     size_y: u8,
     size_z: u8,
-    compressed_data: BlockArray,
+    compressed_data: Vec<u8>,
 }
 
 impl MapChunk {
-    pub fn new(chunk_x: i32, chunk_z: i32, compressed_data: BlockArray) -> Self {
+    pub fn new(chunk_x: i32, chunk_z: i32, blocks: &BlockArray) -> Self {
         Self {
             chunk_x,
             chunk_y: 0,
@@ -25,7 +27,7 @@ impl MapChunk {
             size_x: 15,
             size_y: 127,
             size_z: 15,
-            compressed_data,
+            compressed_data: blocks.compress(),
         }
     }
 }
@@ -38,13 +40,13 @@ pub struct BlockArray {
     sky_light: Vec<u8>,
 }
 
-const CHUNK_WIDTH_X: usize = 16;
-const CHUNK_WIDTH_Z: usize = 16;
-const CHUNK_HEIGHT_Y: usize = 128;
-const CHUNK_TOTAL_BLOCKS: usize = CHUNK_WIDTH_X * CHUNK_WIDTH_Z * CHUNK_HEIGHT_Y;
+pub const CHUNK_WIDTH_X: usize = 16;
+pub const CHUNK_WIDTH_Z: usize = 16;
+pub const CHUNK_HEIGHT_Y: usize = 128;
+pub const CHUNK_TOTAL_BLOCKS: usize = CHUNK_WIDTH_X * CHUNK_WIDTH_Z * CHUNK_HEIGHT_Y;
 
 impl BlockArray {
-    fn compress(self) -> Vec<u8> {
+    fn compress(&self) -> Vec<u8> {
         let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
         encoder.write_all(&self.blocks).unwrap();
         encoder.write_all(&self.metadata).unwrap();
@@ -54,64 +56,197 @@ impl BlockArray {
         encoder.finish().unwrap()
     }
 
-    pub fn new_air() -> Self {
-        Self {
-            blocks: vec![0; CHUNK_TOTAL_BLOCKS],
-            metadata: vec![0; CHUNK_TOTAL_BLOCKS / 2],
-            block_light: vec![0; CHUNK_TOTAL_BLOCKS / 2],
-            sky_light: vec![0xFF; CHUNK_TOTAL_BLOCKS / 2],
+    /// Create a block array from its raw parts, as stored in a chunk file.
+    ///
+    /// Returns [`None`] if any of the arrays has the wrong length.
+    pub fn from_parts(blocks: Vec<u8>, metadata: Vec<u8>, block_light: Vec<u8>, sky_light: Vec<u8>) -> Option<Self> {
+        if blocks.len() != CHUNK_TOTAL_BLOCKS
+            || metadata.len() != CHUNK_TOTAL_BLOCKS / 2
+            || block_light.len() != CHUNK_TOTAL_BLOCKS / 2
+            || sky_light.len() != CHUNK_TOTAL_BLOCKS / 2
+        {
+            return None
         }
+
+        Some(Self {
+            blocks,
+            metadata,
+            block_light,
+            sky_light,
+        })
+    }
+
+    /// The index of a block in the array, from its position within the chunk.
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        y + (z * CHUNK_HEIGHT_Y) + (x * CHUNK_HEIGHT_Y * CHUNK_WIDTH_Z)
+    }
+
+    pub fn block(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.blocks[Self::index(x, y, z)]
+    }
+
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: u8) {
+        self.blocks[Self::index(x, y, z)] = block
     }
 
-    pub fn new_superflat() -> Self {
-        let mut blocks = vec![0; CHUNK_TOTAL_BLOCKS];
-        for y in 0..CHUNK_HEIGHT_Y {
-            for x in 0..CHUNK_WIDTH_X {
-                for z in 0..CHUNK_WIDTH_Z {
-                    let pos = y + (z * (CHUNK_HEIGHT_Y)) + (x * (CHUNK_HEIGHT_Y) * (CHUNK_WIDTH_X));
-                    if y == 7 {
-                        blocks[pos] = Block::Grass as u8;
-                    } else if y > 0 && y < 7 {
-                        blocks[pos] = Block::Dirt as u8;
-                    } else if y == 0 {
-                        blocks[pos] = Block::Bedrock as u8;
-                    } else {
-                        blocks[pos] = Block::Air as u8;
-                    }
+    pub fn metadata(&self, x: usize, y: usize, z: usize) -> u8 {
+        let index = Self::index(x, y, z);
+        (self.metadata[index / 2] >> ((index % 2) * 4)) & 0xF
+    }
+
+    pub fn set_metadata(&mut self, x: usize, y: usize, z: usize, metadata: u8) {
+        let index = Self::index(x, y, z);
+        let shift = (index % 2) * 4;
+        let nibble = &mut self.metadata[index / 2];
+        *nibble = (*nibble & !(0xF << shift)) | ((metadata & 0xF) << shift);
+    }
+
+    pub fn raw_blocks(&self) -> &[u8] {
+        &self.blocks
+    }
+
+    pub fn raw_metadata(&self) -> &[u8] {
+        &self.metadata
+    }
+
+    pub fn raw_block_light(&self) -> &[u8] {
+        &self.block_light
+    }
+
+    pub fn raw_sky_light(&self) -> &[u8] {
+        &self.sky_light
+    }
+
+    /// The light opacity of the block at a position, treating unknown
+    /// blocks as solid.
+    fn light_opacity(&self, x: usize, y: usize, z: usize) -> u8 {
+        Block::from_u8(self.block(x, y, z)).map_or(15, |b| b.light_opacity())
+    }
+
+    /// The lowest Y position above the highest block which blocks light in
+    /// a column.
+    pub fn height(&self, x: usize, z: usize) -> usize {
+        (0..CHUNK_HEIGHT_Y)
+            .rev()
+            .find(|y| self.light_opacity(x, *y, z) != 0)
+            .map_or(0, |y| y + 1)
+    }
+
+    /// The lowest Y position above every block which isn't air in a column.
+    pub fn top(&self, x: usize, z: usize) -> usize {
+        (0..CHUNK_HEIGHT_Y)
+            .rev()
+            .find(|y| self.block(x, *y, z) != Block::Air as u8)
+            .map_or(0, |y| y + 1)
+    }
+
+    /// The [`BlockArray::height`] of every column, indexed by `z << 4 | x`.
+    pub fn height_map(&self) -> Vec<u8> {
+        let mut height_map = vec![0; CHUNK_WIDTH_X * CHUNK_WIDTH_Z];
+        for x in 0..CHUNK_WIDTH_X {
+            for z in 0..CHUNK_WIDTH_Z {
+                height_map[z << 4 | x] = self.height(x, z) as u8;
+            }
+        }
+
+        height_map
+    }
+
+    /// Light every column from the sky down, dimming as it passes through
+    /// blocks. Light spreading sideways is left to the client.
+    pub fn update_sky_light(&mut self) {
+        for x in 0..CHUNK_WIDTH_X {
+            for z in 0..CHUNK_WIDTH_Z {
+                let mut light = 15u8;
+                for y in (0..CHUNK_HEIGHT_Y).rev() {
+                    light = light.saturating_sub(self.light_opacity(x, y, z));
+
+                    let index = Self::index(x, y, z);
+                    let shift = (index % 2) * 4;
+                    let nibble = &mut self.sky_light[index / 2];
+                    *nibble = (*nibble & !(0xF << shift)) | (light << shift);
                 }
             }
         }
+    }
 
-        Self {
+    /// Create a block array from only the block IDs, in the same order as
+    /// they are stored.
+    ///
+    /// Panics if there aren't exactly [`CHUNK_TOTAL_BLOCKS`] blocks.
+    pub fn from_blocks(blocks: Vec<u8>) -> Self {
+        assert_eq!(blocks.len(), CHUNK_TOTAL_BLOCKS);
+
+        let mut array = Self {
             blocks,
-            metadata: vec![0xFF; CHUNK_TOTAL_BLOCKS / 2],
+            metadata: vec![0; CHUNK_TOTAL_BLOCKS / 2],
+            block_light: vec![0; CHUNK_TOTAL_BLOCKS / 2],
+            sky_light: vec![0; CHUNK_TOTAL_BLOCKS / 2],
+        };
+        array.update_sky_light();
+
+        array
+    }
+
+    pub fn new_air() -> Self {
+        Self {
+            blocks: vec![0; CHUNK_TOTAL_BLOCKS],
+            metadata: vec![0; CHUNK_TOTAL_BLOCKS / 2],
             block_light: vec![0; CHUNK_TOTAL_BLOCKS / 2],
             sky_light: vec![0xFF; CHUNK_TOTAL_BLOCKS / 2],
         }
     }
 }
 
-impl ToBytes for MapChunk {
-    type Bytes = Vec<u8>;
+impl Packet for MapChunk {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        let chunk_x = input.read_i32::<BE>()?;
+        let chunk_y = input.read_i16::<BE>()?;
+        let chunk_z = input.read_i32::<BE>()?;
+        let size_x = input.read_u8()?;
+        let size_y = input.read_u8()?;
+        let size_z = input.read_u8()?;
+
+        let length = input.read_i32::<BE>()?;
+        if length < 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "negative chunk data length"))
+        }
+        let mut compressed_data = vec![0; length as usize];
+        input.read_exact(&mut compressed_data)?;
+
+        Ok(Self {
+            chunk_x,
+            chunk_y,
+            chunk_z,
+            size_x,
+            size_y,
+            size_z,
+            compressed_data,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.chunk_x)?;
+        output.write_i16::<BE>(self.chunk_y)?;
+        output.write_i32::<BE>(self.chunk_z)?;
+        output.write_u8(self.size_x)?;
+        output.write_u8(self.size_y)?;
+        output.write_u8(self.size_z)?;
 
-    fn to_bytes(self) -> Self::Bytes {
-        let mut buffer = Vec::new();
-        buffer.write_i32::<BE>(self.chunk_x).unwrap();
-        buffer.write_i16::<BE>(self.chunk_y).unwrap();
-        buffer.write_i32::<BE>(self.chunk_z).unwrap();
-        buffer.write_u8(self.size_x).unwrap();
-        buffer.write_u8(self.size_y).unwrap();
-        buffer.write_u8(self.size_z).unwrap();
+        output.write_i32::<BE>(self.compressed_data.len() as i32)?;
+        output.write_all(&self.compressed_data)?;
 
-        let block_buf = self.compressed_data.compress();
-        buffer.write_i32::<BE>(block_buf.len() as i32).unwrap();
-        buffer.write_all(&block_buf).unwrap();
+        Ok(())
+    }
 
-        buffer
+    fn size(&self) -> usize {
+        4 + 2 + 4 + 3 + 4 + self.compressed_data.len()
     }
 }
 
+/// Tells the client to prepare or forget a chunk.
 #[repr(C)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PreChunk {
     pub x_coord: i32,
     pub z_coord: i32,
@@ -131,20 +266,29 @@ impl PreChunk {
         Self {
             x_coord,
             z_coord,
-            mode: true
+            mode: false
         }
     }
 }
 
-impl ToBytes for PreChunk {
-    type Bytes = [u8; 9];
+impl Packet for PreChunk {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            x_coord: input.read_i32::<BE>()?,
+            z_coord: input.read_i32::<BE>()?,
+            mode: input.read_u8()? != 0,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.x_coord)?;
+        output.write_i32::<BE>(self.z_coord)?;
+        output.write_u8(self.mode as u8)?;
 
-    fn to_bytes(self) -> Self::Bytes {
-        let mut buffer = Vec::new();
-        buffer.write_i32::<BE>(self.x_coord).unwrap();
-        buffer.write_i32::<BE>(self.z_coord).unwrap();
-        buffer.write_u8(self.mode as u8).unwrap();
+        Ok(())
+    }
 
-        buffer.try_into().unwrap()
+    fn size(&self) -> usize {
+        9
     }
 }
@@ -0,0 +1,739 @@
+//! Commands run from the server console or by players with `/` in chat.
+//!
+//! Both go through the same [`CommandRegistry`], so every command works the
+//! same way from either side. More commands can be added to the registry of
+//! a [`GameState`] with [`CommandRegistry::register`].
+
+use std::{collections::BTreeMap, error::Error, fmt::Display, io, net::IpAddr, str::FromStr};
+
+use log::info;
+
+use crate::{
+    blocks_items::{BlockItem, BlockItemID, ItemStack},
+    config::PROPERTIES_FILE,
+    packets::packet17_add_to_inventory::Packet17AddToInventory,
+    position::{PlayerPosition, PLAYER_EYE_HEIGHT},
+    state::GameState,
+};
+
+/// Who a command was run by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSender {
+    Console,
+    /// A player, by entity ID
+    Player(i32),
+}
+
+impl CommandSender {
+    /// The name shown when the sender does something others can see.
+    pub fn name(&self, game_state: &GameState) -> String {
+        match self {
+            Self::Console => "Server".to_string(),
+            Self::Player(eid) => game_state.player_by_eid(*eid)
+                .map_or_else(String::new, |p| p.username().clone()),
+        }
+    }
+
+    /// How much the sender is allowed to do.
+    pub fn permission_level(&self, game_state: &GameState) -> PermissionLevel {
+        match self {
+            Self::Console => PermissionLevel::Console,
+            Self::Player(eid) => match game_state.player_by_eid(*eid) {
+                Some(p) if game_state.access().ops.contains(p.username()) => PermissionLevel::Operator,
+                _ => PermissionLevel::Player,
+            },
+        }
+    }
+
+    /// Send feedback from a command to the sender, in chat for players and
+    /// to the log for the console.
+    pub fn reply(&self, game_state: &mut GameState, message: &str) {
+        match self {
+            Self::Console => info!("{message}"),
+            Self::Player(eid) => game_state.send_chat(*eid, message),
+        }
+    }
+}
+
+/// What a sender has to be to run a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PermissionLevel {
+    /// Any player
+    Player,
+    /// A player who has been made an operator
+    Operator,
+    /// Only the server console
+    Console,
+}
+
+/// The reason a command failed, which is sent back to its sender.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// No command has the name
+    UnknownCommand,
+
+    /// The sender isn't allowed to run the command
+    NoPermission,
+
+    /// The arguments don't match the usage of the command
+    Usage,
+
+    /// An argument couldn't be understood
+    InvalidArgument(String),
+
+    /// The command couldn't be carried out
+    Failed(String),
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownCommand => write!(f, "Unknown command. Type \"help\" for help."),
+            Self::NoPermission => write!(f, "You do not have permission to use this command."),
+            Self::Usage => write!(f, "Wrong arguments for this command."),
+            Self::InvalidArgument(a) => write!(f, "Invalid argument \"{a}\"."),
+            Self::Failed(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+impl Error for CommandError {}
+
+impl From<io::Error> for CommandError {
+    fn from(value: io::Error) -> Self {
+        Self::Failed(value.to_string())
+    }
+}
+
+/// The function which carries out a command.
+pub type CommandHandler = fn(&mut GameState, CommandSender, &mut Arguments) -> Result<(), CommandError>;
+
+/// A command which can be run by name.
+#[derive(Debug, Clone, Copy)]
+pub struct Command {
+    pub name: &'static str,
+    /// The arguments of the command, like `<player> [count]`
+    pub usage: &'static str,
+    /// What the command does, shown by `help`
+    pub description: &'static str,
+    pub permission: PermissionLevel,
+    pub handler: CommandHandler,
+}
+
+impl Command {
+    /// The command with its arguments, as shown to the sender.
+    pub fn usage_text(&self, sender: CommandSender) -> String {
+        let slash = if sender == CommandSender::Console { "" } else { "/" };
+        if self.usage.is_empty() {
+            format!("{slash}{}", self.name)
+        } else {
+            format!("{slash}{} {}", self.name, self.usage)
+        }
+    }
+}
+
+/// Every command the server knows, by name.
+#[derive(Debug, Clone)]
+pub struct CommandRegistry {
+    commands: BTreeMap<String, Command>,
+}
+
+impl CommandRegistry {
+    /// A registry without any commands.
+    pub fn new() -> Self {
+        Self {
+            commands: BTreeMap::new(),
+        }
+    }
+
+    /// Add a command, replacing any command with the same name.
+    pub fn register(&mut self, command: Command) {
+        self.commands.insert(command.name.to_ascii_lowercase(), command);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        self.commands.get(&name.to_ascii_lowercase())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Command> {
+        self.commands.values()
+    }
+}
+
+impl Default for CommandRegistry {
+    /// A registry with the built in commands.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        for command in BUILTIN_COMMANDS {
+            registry.register(command);
+        }
+
+        registry
+    }
+}
+
+/// The arguments given to a command, taken one at a time by its handler.
+#[derive(Debug, Clone)]
+pub struct Arguments<'a> {
+    args: Vec<&'a str>,
+    position: usize,
+}
+
+impl<'a> Arguments<'a> {
+    pub fn new(args: &'a str) -> Self {
+        Self {
+            args: args.split_whitespace().collect(),
+            position: 0,
+        }
+    }
+
+    /// The number of arguments which haven't been taken yet.
+    pub fn remaining(&self) -> usize {
+        self.args.len() - self.position
+    }
+
+    /// Take the next argument.
+    pub fn take(&mut self) -> Result<&'a str, CommandError> {
+        let arg = self.args.get(self.position).ok_or(CommandError::Usage)?;
+        self.position += 1;
+
+        Ok(arg)
+    }
+
+    /// Take the next argument, parsed as a value.
+    pub fn parse<T: FromStr>(&mut self) -> Result<T, CommandError> {
+        let arg = self.take()?;
+        arg.parse().map_err(|_| CommandError::InvalidArgument(arg.to_string()))
+    }
+
+    /// Take the next argument parsed as a value if there is one, or use a
+    /// default.
+    pub fn parse_or<T: FromStr>(&mut self, default: T) -> Result<T, CommandError> {
+        if self.remaining() == 0 {
+            return Ok(default)
+        }
+
+        self.parse()
+    }
+
+    /// Take the next argument as the name of a connected player, giving
+    /// their entity ID.
+    pub fn player(&mut self, game_state: &GameState) -> Result<i32, CommandError> {
+        let name = self.take()?;
+        game_state.online_player(name)
+            .map(|p| p.eid())
+            .ok_or_else(|| CommandError::Failed(format!("Can't find user {name}.")))
+    }
+
+    /// Take every argument which is left, joined by spaces.
+    pub fn rest(&mut self) -> Result<String, CommandError> {
+        if self.remaining() == 0 {
+            return Err(CommandError::Usage)
+        }
+
+        let rest = self.args[self.position..].join(" ");
+        self.position = self.args.len();
+
+        Ok(rest)
+    }
+
+    /// Fail if there are arguments left over.
+    pub fn finish(&self) -> Result<(), CommandError> {
+        match self.remaining() {
+            0 => Ok(()),
+            _ => Err(CommandError::Usage),
+        }
+    }
+}
+
+/// Run a command line, without the leading `/`, sending any errors back to
+/// the sender.
+pub fn dispatch(game_state: &mut GameState, sender: CommandSender, line: &str) {
+    let line = line.trim();
+    let (name, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    if name.is_empty() {
+        return
+    }
+
+    let command = game_state.commands().get(name).copied();
+    let result = match command {
+        None => Err(CommandError::UnknownCommand),
+        Some(c) if sender.permission_level(game_state) < c.permission => Err(CommandError::NoPermission),
+        Some(c) => {
+            let mut args = Arguments::new(args);
+            (c.handler)(game_state, sender, &mut args).and_then(|_| args.finish())
+        }
+    };
+
+    let message = match (result, command) {
+        (Ok(()), _) => return,
+        (Err(CommandError::Usage), Some(command)) => format!("Usage: {}", command.usage_text(sender)),
+        (Err(e), _) => e.to_string(),
+    };
+
+    match sender {
+        CommandSender::Console => sender.reply(game_state, &message),
+        CommandSender::Player(_) => sender.reply(game_state, &format!("§c{message}")),
+    }
+}
+
+const BUILTIN_COMMANDS: [Command; 17] = [
+    Command {
+        name: "help",
+        usage: "[command]",
+        description: "Lists the commands, or shows how to use one",
+        permission: PermissionLevel::Player,
+        handler: help,
+    },
+    Command {
+        name: "list",
+        usage: "",
+        description: "Lists the connected players",
+        permission: PermissionLevel::Player,
+        handler: list,
+    },
+    Command {
+        name: "say",
+        usage: "<message>",
+        description: "Broadcasts a message to every player",
+        permission: PermissionLevel::Operator,
+        handler: say,
+    },
+    Command {
+        name: "kick",
+        usage: "<player>",
+        description: "Disconnects a player",
+        permission: PermissionLevel::Operator,
+        handler: kick,
+    },
+    Command {
+        name: "tp",
+        usage: "[player] <target player> | <player> <x> <y> <z>",
+        description: "Teleports a player to another player or a position",
+        permission: PermissionLevel::Operator,
+        handler: teleport,
+    },
+    Command {
+        name: "setworldspawn",
+        usage: "[<x> <y> <z>]",
+        description: "Moves the spawn point of the world, to where you are by default",
+        permission: PermissionLevel::Operator,
+        handler: set_world_spawn,
+    },
+    Command {
+        name: "time",
+        usage: "<set|add> <ticks> | set <day|night> | <freeze|unfreeze|query>",
+        description: "Changes or stops the time of day",
+        permission: PermissionLevel::Operator,
+        handler: time,
+    },
+    Command {
+        name: "give",
+        usage: "<player> <id> [count] [damage]",
+        description: "Gives a player an item",
+        permission: PermissionLevel::Operator,
+        handler: give,
+    },
+    Command {
+        name: "save-all",
+        usage: "",
+        description: "Saves the world",
+        permission: PermissionLevel::Operator,
+        handler: save_all,
+    },
+    Command {
+        name: "op",
+        usage: "<player>",
+        description: "Makes a player an operator",
+        permission: PermissionLevel::Operator,
+        handler: op,
+    },
+    Command {
+        name: "deop",
+        usage: "<player>",
+        description: "Stops a player being an operator",
+        permission: PermissionLevel::Operator,
+        handler: deop,
+    },
+    Command {
+        name: "ban",
+        usage: "<player>",
+        description: "Bans a player from the server",
+        permission: PermissionLevel::Operator,
+        handler: ban,
+    },
+    Command {
+        name: "pardon",
+        usage: "<player>",
+        description: "Lets a banned player join again",
+        permission: PermissionLevel::Operator,
+        handler: pardon,
+    },
+    Command {
+        name: "ban-ip",
+        usage: "<address|player>",
+        description: "Bans an IP address from the server",
+        permission: PermissionLevel::Operator,
+        handler: ban_ip,
+    },
+    Command {
+        name: "pardon-ip",
+        usage: "<address>",
+        description: "Lets a banned IP address join again",
+        permission: PermissionLevel::Operator,
+        handler: pardon_ip,
+    },
+    Command {
+        name: "whitelist",
+        usage: "<on|off|list|add|remove|reload> [player]",
+        description: "Changes who is allowed to join",
+        permission: PermissionLevel::Operator,
+        handler: whitelist,
+    },
+    Command {
+        name: "stop",
+        usage: "",
+        description: "Saves the world and stops the server",
+        permission: PermissionLevel::Operator,
+        handler: stop,
+    },
+];
+
+fn help(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let level = sender.permission_level(game_state);
+    let lines: Vec<_> = if args.remaining() > 0 {
+        let name = args.take()?;
+        let command = game_state.commands()
+            .get(name)
+            .filter(|c| level >= c.permission)
+            .ok_or(CommandError::UnknownCommand)?;
+
+        vec![
+            format!("Usage: {}", command.usage_text(sender)),
+            command.description.to_string(),
+        ]
+    } else {
+        game_state.commands()
+            .iter()
+            .filter(|c| level >= c.permission)
+            .map(|c| format!("{} - {}", c.usage_text(sender), c.description))
+            .collect()
+    };
+
+    for line in lines {
+        sender.reply(game_state, &line);
+    }
+
+    Ok(())
+}
+
+fn list(game_state: &mut GameState, sender: CommandSender, _args: &mut Arguments) -> Result<(), CommandError> {
+    let players = game_state.online_players();
+    let message = format!("Connected players ({}): {}", players.len(), players.join(", "));
+    sender.reply(game_state, &message);
+
+    Ok(())
+}
+
+fn say(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let message = args.rest()?;
+    let name = sender.name(game_state);
+    game_state.broadcast_chat(&format!("§d[{name}] {message}"));
+
+    Ok(())
+}
+
+fn kick(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let eid = args.player(game_state)?;
+    args.finish()?;
+
+    let name = CommandSender::Player(eid).name(game_state);
+    if let Some(connection) = game_state.connections_mut().get_mut(&eid) {
+        connection.kick("Kicked by admin");
+    }
+    sender.reply(game_state, &format!("Kicking {name}"));
+
+    Ok(())
+}
+
+fn teleport(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let position_of = |game_state: &mut GameState, eid| {
+        game_state.player_by_eid_mut(eid)
+            .map(|p| *p.position())
+            .ok_or(CommandError::Usage)
+    };
+
+    let (eid, position) = match (args.remaining(), sender) {
+        (1, CommandSender::Player(eid)) => {
+            let target = args.player(game_state)?;
+            (eid, position_of(game_state, target)?)
+        }
+        (2, _) => {
+            let eid = args.player(game_state)?;
+            let target = args.player(game_state)?;
+            (eid, position_of(game_state, target)?)
+        }
+        (4, _) => {
+            let eid = args.player(game_state)?;
+            let x: f64 = args.parse()?;
+            let y: f64 = args.parse()?;
+            let z: f64 = args.parse()?;
+            (eid, PlayerPosition {
+                position_x: x,
+                position_y: y,
+                stance: y + PLAYER_EYE_HEIGHT,
+                position_z: z,
+            })
+        }
+        _ => return Err(CommandError::Usage),
+    };
+
+    let name = CommandSender::Player(eid).name(game_state);
+    game_state.teleport(eid, position);
+    sender.reply(game_state, &format!(
+        "Teleported {name} to {:.1}, {:.1}, {:.1}",
+        position.position_x, position.position_y, position.position_z,
+    ));
+
+    Ok(())
+}
+
+fn set_world_spawn(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let (x, y, z) = match (args.remaining(), sender) {
+        (0, CommandSender::Player(eid)) => {
+            let position = game_state.player_by_eid(eid)
+                .map(|p| *p.position())
+                .ok_or(CommandError::Usage)?;
+            (
+                position.position_x.floor() as i32,
+                position.position_y.floor() as i32,
+                position.position_z.floor() as i32,
+            )
+        }
+        (3, _) => (args.parse()?, args.parse()?, args.parse()?),
+        _ => return Err(CommandError::Usage),
+    };
+
+    game_state.set_spawn_point(x, y, z)?;
+    sender.reply(game_state, &format!("Set the world spawn point to {x}, {y}, {z}"));
+
+    Ok(())
+}
+
+/// The length of a day and night, in ticks.
+const DAY_LENGTH: i64 = 24000;
+
+fn time(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let action = args.take()?;
+    let message = match action {
+        "set" | "add" => {
+            let ticks = match args.take()? {
+                "day" if action == "set" => 1000,
+                "night" if action == "set" => 13000,
+                ticks => ticks.parse()
+                    .map_err(|_| CommandError::InvalidArgument(ticks.to_string()))?,
+            };
+            args.finish()?;
+
+            // Setting the time keeps the day count, so it only changes the
+            // time of day
+            let current = game_state.world_time();
+            let time = match action {
+                "set" => current - current.rem_euclid(DAY_LENGTH) + ticks,
+                _ => current + ticks,
+            };
+            game_state.set_world_time(time);
+            format!("Set the time to {}", time.rem_euclid(DAY_LENGTH))
+        }
+        "freeze" | "unfreeze" => {
+            args.finish()?;
+            let frozen = action == "freeze";
+            game_state.set_time_frozen(frozen);
+            match frozen {
+                true => "Stopped the time of day".to_string(),
+                false => "Started the time of day".to_string(),
+            }
+        }
+        "query" => {
+            args.finish()?;
+            let time = game_state.world_time();
+            let state = if game_state.is_time_frozen() { ", frozen" } else { "" };
+            format!("The time is {} on day {}{state}", time.rem_euclid(DAY_LENGTH), time.div_euclid(DAY_LENGTH))
+        }
+        _ => return Err(CommandError::Usage),
+    };
+    sender.reply(game_state, &message);
+
+    Ok(())
+}
+
+fn give(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let eid = args.player(game_state)?;
+    let id: i16 = args.parse()?;
+    let count: u8 = args.parse_or(1)?;
+    let damage: i16 = args.parse_or(0)?;
+
+    let item = BlockItem::from_id(id);
+    if item == BlockItem::Unknown {
+        return Err(CommandError::Failed(format!("There's no item with ID {id}")))
+    }
+
+    let name = CommandSender::Player(eid).name(game_state);
+    let mut item = ItemStack::new(item.id() as i32, count.clamp(1, 64) as i32, damage as i32);
+    let added = game_state.player_by_eid_mut(eid)
+        .map_or(0, |p| p.inventory_mut().add(item));
+    if added == 0 {
+        return Err(CommandError::Failed(format!("{name}'s inventory is full")))
+    }
+
+    item.stack_size = added;
+    game_state.send_to(eid, Packet17AddToInventory { item });
+    sender.reply(game_state, &format!("Giving {name} some {id}"));
+
+    Ok(())
+}
+
+fn save_all(game_state: &mut GameState, sender: CommandSender, _args: &mut Arguments) -> Result<(), CommandError> {
+    sender.reply(game_state, "Forcing save..");
+    game_state.save();
+    sender.reply(game_state, "Save complete.");
+
+    Ok(())
+}
+
+fn stop(game_state: &mut GameState, _sender: CommandSender, _args: &mut Arguments) -> Result<(), CommandError> {
+    game_state.stop();
+
+    Ok(())
+}
+
+fn op(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let name = args.take()?;
+    if !game_state.access_mut().ops.add(name)? {
+        return Err(CommandError::Failed(format!("{name} is already an operator")))
+    }
+
+    if let Some(eid) = game_state.online_player(name).map(|p| p.eid()) {
+        game_state.send_chat(eid, "§eYou are now op!");
+    }
+    sender.reply(game_state, &format!("Opping {name}"));
+
+    Ok(())
+}
+
+fn deop(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let name = args.take()?;
+    if !game_state.access_mut().ops.remove(name)? {
+        return Err(CommandError::Failed(format!("{name} isn't an operator")))
+    }
+
+    if let Some(eid) = game_state.online_player(name).map(|p| p.eid()) {
+        game_state.send_chat(eid, "§eYou are no longer op!");
+    }
+    sender.reply(game_state, &format!("De-opping {name}"));
+
+    Ok(())
+}
+
+fn ban(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let name = args.take()?;
+    if !game_state.access_mut().banned_players.add(name)? {
+        return Err(CommandError::Failed(format!("{name} is already banned")))
+    }
+
+    if let Some(eid) = game_state.online_player(name).map(|p| p.eid()) {
+        if let Some(connection) = game_state.connections_mut().get_mut(&eid) {
+            connection.kick("Banned by admin");
+        }
+    }
+    sender.reply(game_state, &format!("Banning {name}"));
+
+    Ok(())
+}
+
+fn pardon(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let name = args.take()?;
+    if !game_state.access_mut().banned_players.remove(name)? {
+        return Err(CommandError::Failed(format!("{name} isn't banned")))
+    }
+    sender.reply(game_state, &format!("Pardoning {name}"));
+
+    Ok(())
+}
+
+fn ban_ip(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let target = args.take()?;
+    let address = match target.parse::<IpAddr>() {
+        Ok(a) => a,
+        Err(_) => game_state.online_player(target)
+            .map(|p| p.address())
+            .ok_or_else(|| CommandError::Failed(format!("{target} isn't an IP address or a connected player")))?,
+    };
+
+    if !game_state.access_mut().banned_ips.add(&address.to_string())? {
+        return Err(CommandError::Failed(format!("{address} is already banned")))
+    }
+
+    let eids: Vec<_> = game_state.player_list()
+        .values()
+        .filter(|p| p.address() == address)
+        .map(|p| p.eid())
+        .collect();
+    for eid in eids {
+        if let Some(connection) = game_state.connections_mut().get_mut(&eid) {
+            connection.kick("Banned by admin");
+        }
+    }
+    sender.reply(game_state, &format!("Banning ip {address}"));
+
+    Ok(())
+}
+
+fn pardon_ip(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let address = args.take()?;
+    if !game_state.access_mut().banned_ips.remove(address)? {
+        return Err(CommandError::Failed(format!("{address} isn't banned")))
+    }
+    sender.reply(game_state, &format!("Pardoning ip {address}"));
+
+    Ok(())
+}
+
+fn whitelist(game_state: &mut GameState, sender: CommandSender, args: &mut Arguments) -> Result<(), CommandError> {
+    let message = match args.take()? {
+        action @ ("on" | "off") => {
+            game_state.config_mut().white_list = action == "on";
+            game_state.config().save(PROPERTIES_FILE)?;
+            format!("Turned {action} white-listing")
+        }
+        "list" => {
+            let names: Vec<_> = game_state.access().white_list.names().collect();
+            format!("White-listed players: {}", names.join(", "))
+        }
+        "add" => {
+            let name = args.take()?;
+            if !game_state.access_mut().white_list.add(name)? {
+                return Err(CommandError::Failed(format!("{name} is already white-listed")))
+            }
+            format!("Added {name} to white-list")
+        }
+        "remove" => {
+            let name = args.take()?;
+            if !game_state.access_mut().white_list.remove(name)? {
+                return Err(CommandError::Failed(format!("{name} isn't white-listed")))
+            }
+            format!("Removed {name} from white-list")
+        }
+        "reload" => {
+            let access = game_state.access_mut();
+            for list in [&mut access.ops, &mut access.white_list, &mut access.banned_players, &mut access.banned_ips] {
+                list.reload()?;
+            }
+            "Reloaded the player lists".to_string()
+        }
+        _ => return Err(CommandError::Usage),
+    };
+    sender.reply(game_state, &message);
+
+    Ok(())
+}
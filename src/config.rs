@@ -0,0 +1,267 @@
+use std::{collections::BTreeMap, fmt::Display, fs, io, path::Path, str::FromStr};
+
+use log::LevelFilter;
+
+use crate::connection::PROTOCOL_VERSION;
+
+/// The file the settings are read from.
+pub const PROPERTIES_FILE: &str = "server.properties";
+
+/// The kind of terrain a new world is generated with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LevelType {
+    /// Hills, oceans and caves like the original
+    #[default]
+    Default,
+
+    /// Flat layers from the `generator-settings` preset
+    Flat,
+}
+
+impl FromStr for LevelType {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_uppercase().as_str() {
+            "DEFAULT" => Ok(Self::Default),
+            "FLAT" => Ok(Self::Flat),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for LevelType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "DEFAULT"),
+            Self::Flat => write!(f, "FLAT"),
+        }
+    }
+}
+
+/// Settings for the server, read from a `server.properties` file with the
+/// same keys as the original server.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// The address to listen on, or every address if it is empty
+    pub server_ip: String,
+    pub server_port: u16,
+
+    /// The directory the world is stored in
+    pub level_name: String,
+    pub level_type: LevelType,
+
+    /// The layers of a flat world, see
+    /// [`FlatGenerator`](crate::generator::flat::FlatGenerator). An empty
+    /// preset uses the default layers.
+    pub generator_settings: String,
+
+    pub max_players: usize,
+
+    /// Whether players have to be logged in to their account. The server
+    /// won't start in online mode unless `auth_server` is set.
+    pub online_mode: bool,
+
+    /// The `checkserver.jsp` endpoint logins are checked with in online
+    /// mode. There is no default, since the original one is gone.
+    pub auth_server: String,
+
+    /// The protocol versions clients are allowed to log in with
+    pub protocol_versions: Vec<i32>,
+
+    /// How many ticks a player can send nothing for before they are
+    /// disconnected
+    pub connection_timeout: u64,
+
+    pub spawn_monsters: bool,
+    pub spawn_animals: bool,
+    pub pvp: bool,
+
+    /// Only let players on the whitelist join
+    pub white_list: bool,
+
+    /// How many chunks around them players are sent, from 3 to 15
+    pub view_distance: i32,
+
+    /// How many blocks from the spawn point new players may be placed, at
+    /// random
+    pub spawn_radius: i32,
+
+    /// The most detailed messages written to the log
+    pub log_level: LevelFilter,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            server_ip: String::new(),
+            server_port: 25565,
+            level_name: "world".to_string(),
+            level_type: LevelType::Default,
+            generator_settings: String::new(),
+            max_players: 20,
+            online_mode: false,
+            auth_server: String::new(),
+            protocol_versions: vec![PROTOCOL_VERSION],
+            connection_timeout: 1200,
+            spawn_monsters: true,
+            spawn_animals: true,
+            pvp: true,
+            white_list: false,
+            view_distance: 10,
+            spawn_radius: 0,
+            log_level: LevelFilter::Info,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Read the settings from a properties file, using the defaults for
+    /// anything which isn't in it.
+    ///
+    /// Like the original, every setting which is missing is written back to
+    /// the file, so a file with the defaults is created on the first run.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let path = path.as_ref();
+        let mut properties = read_properties(path)?;
+        let config = Self::from_properties(&properties)?;
+
+        let values = config.to_properties();
+        if values.keys().any(|k| !properties.contains_key(k)) {
+            for (key, value) in values {
+                properties.entry(key).or_insert(value);
+            }
+            write_properties(path, &properties)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Write the settings to a properties file, keeping any other settings
+    /// which are in it.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        let path = path.as_ref();
+        let mut properties = read_properties(path)?;
+        properties.extend(self.to_properties());
+
+        write_properties(path, &properties)
+    }
+
+    fn from_properties(properties: &BTreeMap<String, String>) -> Result<Self, io::Error> {
+        let mut config = Self::default();
+
+        macro_rules! set {
+            ($field:ident, $key:literal) => {
+                if let Some(value) = parse_value(properties, $key)? {
+                    config.$field = value;
+                }
+            };
+        }
+
+        set!(server_ip, "server-ip");
+        set!(server_port, "server-port");
+        set!(level_name, "level-name");
+        set!(level_type, "level-type");
+        set!(generator_settings, "generator-settings");
+        set!(max_players, "max-players");
+        set!(online_mode, "online-mode");
+        set!(auth_server, "auth-server");
+        set!(connection_timeout, "connection-timeout");
+        set!(spawn_monsters, "spawn-monsters");
+        set!(spawn_animals, "spawn-animals");
+        set!(pvp, "pvp");
+        set!(white_list, "white-list");
+        set!(view_distance, "view-distance");
+        set!(spawn_radius, "spawn-radius");
+        set!(log_level, "log-level");
+
+        if let Some(versions) = properties.get("protocol-versions") {
+            config.protocol_versions = versions.split(',')
+                .map(|v| v.trim().parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| invalid_value("protocol-versions", versions))?;
+        }
+
+        config.view_distance = config.view_distance.clamp(3, 15);
+        config.spawn_radius = config.spawn_radius.max(0);
+
+        Ok(config)
+    }
+
+    fn to_properties(&self) -> BTreeMap<String, String> {
+        [
+            ("server-ip", self.server_ip.clone()),
+            ("server-port", self.server_port.to_string()),
+            ("level-name", self.level_name.clone()),
+            ("level-type", self.level_type.to_string()),
+            ("generator-settings", self.generator_settings.clone()),
+            ("max-players", self.max_players.to_string()),
+            ("online-mode", self.online_mode.to_string()),
+            ("auth-server", self.auth_server.clone()),
+            ("connection-timeout", self.connection_timeout.to_string()),
+            ("protocol-versions", self.protocol_versions.iter().map(i32::to_string).collect::<Vec<_>>().join(",")),
+            ("spawn-monsters", self.spawn_monsters.to_string()),
+            ("spawn-animals", self.spawn_animals.to_string()),
+            ("pvp", self.pvp.to_string()),
+            ("white-list", self.white_list.to_string()),
+            ("view-distance", self.view_distance.to_string()),
+            ("spawn-radius", self.spawn_radius.to_string()),
+            ("log-level", self.log_level.to_string().to_lowercase()),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect()
+    }
+
+    /// The address to listen for connections on.
+    pub fn bind_address(&self) -> (&str, u16) {
+        let ip = if self.server_ip.is_empty() {
+            "0.0.0.0"
+        } else {
+            &self.server_ip
+        };
+
+        (ip, self.server_port)
+    }
+}
+
+/// Read a properties file, which is empty if it doesn't exist.
+fn read_properties(path: &Path) -> Result<BTreeMap<String, String>, io::Error> {
+    match fs::read_to_string(path) {
+        Ok(c) => Ok(parse_properties(&c)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Split the `key=value` lines of a properties file, skipping blank lines and
+/// `#` comments.
+fn parse_properties(contents: &str) -> BTreeMap<String, String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with('!'))
+        .filter_map(|l| l.split_once('=').or(Some((l, ""))))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect()
+}
+
+fn write_properties(path: &Path, properties: &BTreeMap<String, String>) -> Result<(), io::Error> {
+    let mut contents = String::from("#Minecraft server properties\n");
+    for (key, value) in properties {
+        contents.push_str(&format!("{key}={value}\n"));
+    }
+
+    fs::write(path, contents)
+}
+
+/// Parse the value of a key, if it is set.
+fn parse_value<T: FromStr>(properties: &BTreeMap<String, String>, key: &str) -> Result<Option<T>, io::Error> {
+    properties.get(key)
+        .map(|v| v.parse().map_err(|_| invalid_value(key, v)))
+        .transpose()
+}
+
+fn invalid_value(key: &str, value: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Invalid value \"{value}\" for {key}"))
+}
@@ -0,0 +1,289 @@
+use std::{
+    io::{self, BufReader, BufWriter, Write},
+    net::{Shutdown, TcpStream},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+use base16ct::lower::encode_string;
+use log::{debug, error, info};
+use rand::random;
+
+use crate::{
+    auth::SessionServer,
+    entity_id::ENTITY_ID,
+    error::ProtocolError,
+    mcstring::MCString,
+    packets::{
+        packet1_login::Packet1Login,
+        packet255_kick_disconnect::Packet255KickDisconnect,
+        packet2_handshake::Packet2Handshake,
+        ClientboundPacket, Packet, RecordingReader, ServerboundPacket,
+    },
+    server::ServerEvent,
+    state::PlayerState,
+};
+
+/// The version of the protocol the server speaks, used by Beta 1.1_02.
+pub const PROTOCOL_VERSION: i32 = 8;
+
+/// How long a client may stay silent before it has logged in. After that the
+/// server thread disconnects players who stop sending packets.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What a client has to pass to be allowed to log in.
+#[derive(Debug, Clone)]
+pub struct LoginPolicy {
+    /// The protocol versions clients may use, which normally only includes
+    /// [`PROTOCOL_VERSION`]
+    pub protocol_versions: Vec<i32>,
+
+    /// The session server logins are checked with, in online mode
+    pub session_server: Option<SessionServer>,
+}
+
+impl LoginPolicy {
+    /// The reason a client using a protocol version is kicked with, if it
+    /// isn't allowed.
+    pub fn check_protocol_version(&self, version: i32) -> Result<(), &'static str> {
+        if self.protocol_versions.contains(&version) {
+            Ok(())
+        } else if version < PROTOCOL_VERSION {
+            Err("Outdated client!")
+        } else {
+            Err("Outdated server!")
+        }
+    }
+}
+
+/// The sending half of a connection to a client.
+///
+/// Packets are queued with [`Connection::send`] and handed to the writer
+/// thread all at once by [`Connection::flush`] at the end of every tick.
+#[derive(Debug)]
+pub struct Connection {
+    sender: Sender<ClientboundPacket>,
+    queue: Vec<ClientboundPacket>,
+}
+
+impl Connection {
+    /// Start a writer thread for the stream, returning the sending half.
+    pub fn open(stream: &TcpStream) -> Result<Self, io::Error> {
+        let (sender, receiver) = mpsc::channel();
+        let stream = stream.try_clone()?;
+
+        thread::spawn(move || {
+            if let Err(e) = write_loop(&stream, receiver) {
+                debug!("Writing to {:?} failed: {e}", stream.peer_addr());
+            }
+            let _ = stream.shutdown(Shutdown::Both);
+        });
+
+        Ok(Self {
+            sender,
+            queue: Vec::new(),
+        })
+    }
+
+    /// Queue a packet to be sent on the next flush.
+    pub fn send<P: Into<ClientboundPacket>>(&mut self, packet: P) {
+        self.queue.push(packet.into())
+    }
+
+    /// Send a packet immediately, without waiting for the next flush.
+    pub fn send_now<P: Into<ClientboundPacket>>(&mut self, packet: P) {
+        self.send(packet);
+        self.flush();
+    }
+
+    /// Hand every queued packet over to the writer thread.
+    pub fn flush(&mut self) {
+        for packet in self.queue.drain(..) {
+            // If the writer has stopped the connection is already closing
+            let _ = self.sender.send(packet);
+        }
+    }
+
+    /// Send a `Disconnect` packet with a reason, after which the connection
+    /// is closed.
+    pub fn kick(&mut self, reason: &str) {
+        let reason = MCString::try_from(reason).unwrap_or_default();
+        self.send_now(Packet255KickDisconnect { reason });
+    }
+}
+
+/// Write packets out to the stream until the channel is closed or the client
+/// is disconnected.
+fn write_loop(stream: &TcpStream, packets: Receiver<ClientboundPacket>) -> Result<(), io::Error> {
+    let mut writer = BufWriter::new(stream);
+    while let Ok(packet) = packets.recv() {
+        // Write everything that is waiting before flushing the stream
+        for packet in std::iter::once(packet).chain(packets.try_iter()) {
+            packet.write_into(&mut writer)?;
+
+            if let ClientboundPacket::Disconnect(_) = packet {
+                writer.flush()?;
+                return Ok(())
+            }
+        }
+
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+/// Read packets from a client until it disconnects.
+///
+/// The handshake and login are handled here, after which every packet is
+/// passed on to the server thread. Logins have to pass the checks of the
+/// policy. However the connection ends, the server thread is told about it
+/// once the player has joined.
+pub fn player_loop(
+    stream: TcpStream,
+    events: Sender<ServerEvent>,
+    policy: LoginPolicy,
+) -> Result<(), io::Error> {
+    let mut eid = None;
+    let result = read_loop(&stream, &events, &policy, &mut eid);
+
+    if let Some(eid) = eid {
+        let reason = match &result {
+            Ok(reason) => reason.clone(),
+            Err(e) => e.to_string(),
+        };
+        let _ = events.send(ServerEvent::Disconnected { eid, reason });
+    }
+
+    result.map(|_| ())
+}
+
+/// Handle the packets of a client, setting the entity ID once it has logged
+/// in. Returns why the connection ended.
+fn read_loop(
+    stream: &TcpStream,
+    events: &Sender<ServerEvent>,
+    policy: &LoginPolicy,
+    eid: &mut Option<i32>,
+) -> Result<String, io::Error> {
+    stream.set_read_timeout(Some(LOGIN_TIMEOUT))?;
+    let mut connection = Connection::open(stream)?;
+    let mut reader = RecordingReader::new(BufReader::new(stream.try_clone()?));
+    let mut server_hash = None;
+
+    loop {
+        reader.clear();
+        let packet = match ServerboundPacket::decode(&mut reader) {
+            Ok(p) => p,
+            Err(ProtocolError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                return Ok("End of stream".to_string())
+            }
+            Err(ProtocolError::Io(e)) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {
+                connection.kick("Timed out");
+                return Ok("Timed out".to_string())
+            }
+            Err(ProtocolError::Io(e)) => {
+                debug!("Reading from {:?} failed: {e}", stream.peer_addr());
+                return Ok(e.to_string())
+            }
+            Err(e) => {
+                error!("{e}, bytes: {:02X?}", reader.recorded());
+                if let Some(reason) = e.disconnect_reason() {
+                    connection.kick(&reason);
+                }
+                return Ok(e.to_string())
+            }
+        };
+
+        if let ServerboundPacket::Disconnect(disconnect) = &packet {
+            return Ok(disconnect.reason.to_string())
+        }
+
+        if let Some(eid) = *eid {
+            if events.send(ServerEvent::Packet { eid, packet }).is_err() {
+                return Ok("Server closed".to_string())
+            }
+            continue;
+        }
+
+        match packet {
+            ServerboundPacket::Handshake(handshake) => {
+                let hash = if policy.session_server.is_some() {
+                    let random_number = random::<u128>();
+                    encode_string(md5::compute(random_number.to_le_bytes()).as_slice())
+                } else {
+                    "-".to_string()
+                };
+                connection.send_now(Packet2Handshake {
+                    username: MCString::try_from(hash.as_str()).unwrap(),
+                });
+                server_hash = Some(hash);
+                info!("Handshake with {} successful", handshake.username);
+            }
+            ServerboundPacket::Login(login_info) => {
+                if let Err(reason) = policy.check_protocol_version(login_info.protocol_version) {
+                    info!(
+                        "Disconnecting {} [{}]: {reason} (protocol version {})",
+                        login_info.username,
+                        stream.peer_addr()?,
+                        login_info.protocol_version,
+                    );
+                    connection.kick(reason);
+                    return Ok(reason.to_string())
+                }
+
+                if let Some(session_server) = &policy.session_server {
+                    let username = login_info.username.to_string();
+                    if !verify_login(session_server, &username, server_hash.as_deref()) {
+                        connection.kick("Failed to verify username!");
+                        return Ok("Failed to verify username!".to_string())
+                    }
+                }
+
+                stream.set_read_timeout(None)?;
+                let player = login(stream, login_info)?;
+                *eid = Some(player.eid());
+                let connection = Connection {
+                    sender: connection.sender.clone(),
+                    queue: Vec::new(),
+                };
+                if events.send(ServerEvent::Join { player: Box::new(player), connection }).is_err() {
+                    return Ok("Server closed".to_string())
+                }
+            }
+            p => debug!("Ignoring {:?} packet before login", p.id()),
+        }
+    }
+}
+
+/// Check with the session server that a player is logged in to their
+/// account.
+fn verify_login(session_server: &SessionServer, username: &str, server_hash: Option<&str>) -> bool {
+    let Some(server_hash) = server_hash else {
+        info!("{username} tried to log in without a handshake");
+        return false
+    };
+
+    match session_server.has_joined(username, server_hash) {
+        Ok(true) => true,
+        Ok(false) => {
+            info!("{username} failed to verify their username");
+            false
+        }
+        Err(e) => {
+            error!("Checking the login of {username} failed: {e}");
+            false
+        }
+    }
+}
+
+/// Accept a login, creating the state of the new player. The server thread
+/// replies to the login once it has placed the player in the world.
+fn login(stream: &TcpStream, login_info: Packet1Login) -> Result<PlayerState, io::Error> {
+    let eid = ENTITY_ID.get();
+    let address = stream.peer_addr()?;
+    info!("{} [{}] logged in with entity id {}", login_info.username, address, eid);
+
+    Ok(PlayerState::new(login_info.username.to_string(), eid, address.ip()))
+}
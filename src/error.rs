@@ -0,0 +1,65 @@
+use std::{error::Error, fmt::Display, io};
+
+use crate::packets::Command;
+
+/// An error encountered while talking to a client.
+#[derive(Debug)]
+pub enum ProtocolError {
+    /// The connection could not be read from or written to
+    Io(io::Error),
+
+    /// The packet ID is not part of the protocol
+    UnknownPacket(u8),
+
+    /// The packet exists, but is never sent in this direction
+    WrongDirection(Command),
+
+    /// The contents of the packet could not be decoded
+    Malformed(Command, String),
+}
+
+impl ProtocolError {
+    /// The reason sent to the client in a `Disconnect` packet, if the
+    /// connection is still usable.
+    pub fn disconnect_reason(&self) -> Option<String> {
+        match self {
+            Self::Io(_) => None,
+            e => Some(e.to_string()),
+        }
+    }
+}
+
+impl Display for ProtocolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::UnknownPacket(id) => write!(f, "Bad packet id {id}"),
+            Self::WrongDirection(c) => write!(f, "Unexpected packet {c:?} (id {})", *c as u8),
+            Self::Malformed(c, reason) => write!(f, "Malformed packet {c:?}: {reason}"),
+        }
+    }
+}
+
+impl Error for ProtocolError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ProtocolError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<ProtocolError> for io::Error {
+    fn from(value: ProtocolError) -> Self {
+        match value {
+            ProtocolError::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
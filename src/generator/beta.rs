@@ -0,0 +1,414 @@
+//! A port of the terrain generator of the original game.
+//!
+//! The terrain, beaches and caves follow the original step by step, so a
+//! seed gives the same landscape. Population only places ores and trees,
+//! without the lakes, dungeons and plants of the original, so those
+//! features end up in different places.
+
+use crate::{
+    blocks_items::Block,
+    chunk::{BlockArray, CHUNK_TOTAL_BLOCKS},
+};
+
+use super::{
+    biome::{Biome, BiomeSource, Climate},
+    caves::Caves,
+    features::{Minable, Tree},
+    noise::OctaveNoise,
+    random::JavaRandom,
+    PopulationArea,
+    WorldGenerator,
+};
+
+/// The height of the sea.
+pub const SEA_LEVEL: i32 = 64;
+
+/// The veins placed in every chunk as `(block, size, count, max height)`.
+const VEINS: [(Block, i32, i32, i32); 7] = [
+    (Block::Dirt, 32, 20, 128),
+    (Block::Gravel, 32, 10, 128),
+    (Block::OreCoal, 16, 20, 128),
+    (Block::OreIron, 8, 20, 64),
+    (Block::OreGold, 8, 2, 32),
+    (Block::OreRedstone, 7, 8, 16),
+    (Block::OreDiamond, 7, 1, 16),
+];
+
+/// Generates hills, oceans, beaches and caves from Perlin noise, the same as
+/// the original.
+#[derive(Debug, Clone)]
+pub struct BetaGenerator {
+    seed: i64,
+    biomes: BiomeSource,
+    caves: Caves,
+    low: OctaveNoise,
+    high: OctaveNoise,
+    selector: OctaveNoise,
+    beach: OctaveNoise,
+    surface_depth: OctaveNoise,
+    scale: OctaveNoise,
+    depth: OctaveNoise,
+    forest: OctaveNoise,
+}
+
+impl BetaGenerator {
+    pub fn new(seed: i64) -> Self {
+        let mut random = JavaRandom::new(seed);
+
+        // These have to be created in this order to match the original
+        Self {
+            seed,
+            biomes: BiomeSource::new(seed),
+            caves: Caves::new(seed),
+            low: OctaveNoise::new(&mut random, 16),
+            high: OctaveNoise::new(&mut random, 16),
+            selector: OctaveNoise::new(&mut random, 8),
+            beach: OctaveNoise::new(&mut random, 4),
+            surface_depth: OctaveNoise::new(&mut random, 4),
+            scale: OctaveNoise::new(&mut random, 10),
+            depth: OctaveNoise::new(&mut random, 16),
+            forest: OctaveNoise::new(&mut random, 8),
+        }
+    }
+
+    /// The density of the terrain at every fourth block horizontally and
+    /// every eighth block vertically, where anything above 0 is solid.
+    fn density(&self, chunk_x: i32, chunk_z: i32, climate: &Climate) -> Vec<f64> {
+        const SIZE_XZ: usize = 5;
+        const SIZE_Y: usize = 17;
+        const SCALE: f64 = 684.412;
+
+        let position = ((chunk_x * 4) as f64, 0.0, (chunk_z * 4) as f64);
+        let size = (SIZE_XZ, SIZE_Y, SIZE_XZ);
+        let scale_noise = self.scale.grid_2d((position.0, position.2), (SIZE_XZ, SIZE_XZ), (1.121, 1.121));
+        let depth_noise = self.depth.grid_2d((position.0, position.2), (SIZE_XZ, SIZE_XZ), (200.0, 200.0));
+        let selector = self.selector.grid(position, size, (SCALE / 80.0, SCALE / 160.0, SCALE / 80.0));
+        let low = self.low.grid(position, size, (SCALE, SCALE, SCALE));
+        let high = self.high.grid(position, size, (SCALE, SCALE, SCALE));
+
+        let mut density = vec![0.0; SIZE_XZ * SIZE_Y * SIZE_XZ];
+        let mut column = 0;
+        let mut index = 0;
+        let step = 16 / SIZE_XZ;
+        for x in 0..SIZE_XZ {
+            let climate_x = x * step + step / 2;
+            for z in 0..SIZE_XZ {
+                let climate_z = z * step + step / 2;
+                let temperature = climate.temperature[climate_x * 16 + climate_z];
+                let humidity = climate.humidity[climate_x * 16 + climate_z] * temperature;
+
+                let mut dryness = 1.0 - humidity;
+                dryness *= dryness;
+                dryness *= dryness;
+                dryness = 1.0 - dryness;
+
+                let mut scale = (scale_noise[column] + 256.0) / 512.0;
+                scale *= dryness;
+                if scale > 1.0 {
+                    scale = 1.0;
+                }
+
+                let mut depth = depth_noise[column] / 8000.0;
+                if depth < 0.0 {
+                    depth = -depth * 0.3;
+                }
+                depth = depth * 3.0 - 2.0;
+                if depth < 0.0 {
+                    depth /= 2.0;
+                    if depth < -1.0 {
+                        depth = -1.0;
+                    }
+                    depth /= 1.4;
+                    depth /= 2.0;
+                    scale = 0.0;
+                } else {
+                    if depth > 1.0 {
+                        depth = 1.0;
+                    }
+                    depth /= 8.0;
+                }
+
+                if scale < 0.0 {
+                    scale = 0.0;
+                }
+                scale += 0.5;
+                depth = depth * SIZE_Y as f64 / 16.0;
+                let centre = SIZE_Y as f64 / 2.0 + depth * 4.0;
+                column += 1;
+
+                for y in 0..SIZE_Y {
+                    let mut falloff = (y as f64 - centre) * 12.0 / scale;
+                    if falloff < 0.0 {
+                        falloff *= 4.0;
+                    }
+
+                    let low = low[index] / 512.0;
+                    let high = high[index] / 512.0;
+                    let selector = (selector[index] / 10.0 + 1.0) / 2.0;
+                    let mut value = if selector < 0.0 {
+                        low
+                    } else if selector > 1.0 {
+                        high
+                    } else {
+                        low + (high - low) * selector
+                    };
+                    value -= falloff;
+
+                    // Close off the top of the world
+                    if y > SIZE_Y - 4 {
+                        let top = (y - (SIZE_Y - 4)) as f32 / 3.0;
+                        value = value * (1.0 - top as f64) + -10.0 * top as f64;
+                    }
+
+                    density[index] = value;
+                    index += 1;
+                }
+            }
+        }
+
+        density
+    }
+
+    /// Fill in stone wherever the density is above 0, and water below the
+    /// sea level, freezing it in cold places.
+    fn shape_terrain(&self, chunk_x: i32, chunk_z: i32, blocks: &mut [u8], climate: &Climate) {
+        let density = self.density(chunk_x, chunk_z, climate);
+        let at = |x: usize, z: usize, y: usize| density[(x * 5 + z) * 17 + y];
+
+        for cell_x in 0..4 {
+            for cell_z in 0..4 {
+                for cell_y in 0..16 {
+                    let mut d1 = at(cell_x, cell_z, cell_y);
+                    let mut d2 = at(cell_x, cell_z + 1, cell_y);
+                    let mut d3 = at(cell_x + 1, cell_z, cell_y);
+                    let mut d4 = at(cell_x + 1, cell_z + 1, cell_y);
+                    let step1 = (at(cell_x, cell_z, cell_y + 1) - d1) * 0.125;
+                    let step2 = (at(cell_x, cell_z + 1, cell_y + 1) - d2) * 0.125;
+                    let step3 = (at(cell_x + 1, cell_z, cell_y + 1) - d3) * 0.125;
+                    let step4 = (at(cell_x + 1, cell_z + 1, cell_y + 1) - d4) * 0.125;
+
+                    for sub_y in 0..8 {
+                        let y = cell_y * 8 + sub_y;
+                        let mut row_start = d1;
+                        let mut row_end = d2;
+                        let row_start_step = (d3 - d1) * 0.25;
+                        let row_end_step = (d4 - d2) * 0.25;
+
+                        for sub_x in 0..4 {
+                            let x = cell_x * 4 + sub_x;
+                            let mut value = row_start;
+                            let value_step = (row_end - row_start) * 0.25;
+
+                            for sub_z in 0..4 {
+                                let z = cell_z * 4 + sub_z;
+                                let temperature = climate.temperature[x * 16 + z];
+
+                                let mut block = Block::Air;
+                                if (y as i32) < SEA_LEVEL {
+                                    block = if temperature < 0.5 && y as i32 >= SEA_LEVEL - 1 {
+                                        Block::BlockIce
+                                    } else {
+                                        Block::WaterStill
+                                    };
+                                }
+                                if value > 0.0 {
+                                    block = Block::Stone;
+                                }
+
+                                blocks[x << 11 | z << 7 | y] = block as u8;
+                                value += value_step;
+                            }
+
+                            row_start += row_start_step;
+                            row_end += row_end_step;
+                        }
+
+                        d1 += step1;
+                        d2 += step2;
+                        d3 += step3;
+                        d4 += step4;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Cover the stone with the blocks of each biome, with sand and gravel
+    /// beaches near the sea, and bedrock at the bottom.
+    fn replace_surface(
+        &self,
+        chunk_x: i32,
+        chunk_z: i32,
+        blocks: &mut [u8],
+        climate: &Climate,
+        random: &mut JavaRandom,
+    ) {
+        const SCALE: f64 = 0.03125;
+
+        let (x, z) = ((chunk_x * 16) as f64, (chunk_z * 16) as f64);
+        let sand = self.beach.grid((x, z, 0.0), (16, 16, 1), (SCALE, SCALE, 1.0));
+        let gravel = self.beach.grid((x, 109.0134, z), (16, 1, 16), (SCALE, 1.0, SCALE));
+        let stone = self.surface_depth.grid((x, z, 0.0), (16, 16, 1), (SCALE * 2.0, SCALE * 2.0, SCALE * 2.0));
+
+        for z in 0..16 {
+            for x in 0..16 {
+                let column = x * 16 + z;
+                let biome: Biome = climate.biomes[column];
+                let sand = sand[column] + random.next_double() * 0.2 > 0.0;
+                let gravel = gravel[column] + random.next_double() * 0.2 > 3.0;
+                let depth = (stone[column] / 3.0 + 3.0 + random.next_double() * 0.25) as i32;
+
+                let mut remaining = -1;
+                let mut top = biome.top_block();
+                let mut filler = biome.filler_block();
+
+                for y in (0..128).rev() {
+                    let index = (x * 16 + z) * 128 + y as usize;
+                    if y <= random.next_int(5) {
+                        blocks[index] = Block::Bedrock as u8;
+                        continue
+                    }
+
+                    let block = blocks[index];
+                    if block == Block::Air as u8 {
+                        remaining = -1;
+                        continue
+                    }
+                    if block != Block::Stone as u8 {
+                        continue
+                    }
+
+                    if remaining == -1 {
+                        if depth <= 0 {
+                            top = Block::Air;
+                            filler = Block::Stone;
+                        } else if (SEA_LEVEL - 4..=SEA_LEVEL + 1).contains(&y) {
+                            top = biome.top_block();
+                            filler = biome.filler_block();
+                            if gravel {
+                                top = Block::Air;
+                                filler = Block::Gravel;
+                            }
+                            if sand {
+                                top = Block::Sand;
+                                filler = Block::Sand;
+                            }
+                        }
+
+                        if y < SEA_LEVEL && top == Block::Air {
+                            top = Block::WaterStill;
+                        }
+
+                        remaining = depth;
+                        blocks[index] = if y >= SEA_LEVEL - 1 { top } else { filler } as u8;
+                    } else if remaining > 0 {
+                        remaining -= 1;
+                        blocks[index] = filler as u8;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl WorldGenerator for BetaGenerator {
+    fn generate(&self, chunk_x: i32, chunk_z: i32) -> BlockArray {
+        let mut random = JavaRandom::new(
+            (chunk_x as i64).wrapping_mul(341873128712)
+                .wrapping_add((chunk_z as i64).wrapping_mul(132897987541))
+        );
+        let climate = self.biomes.climate(chunk_x * 16, chunk_z * 16, 16);
+
+        let mut blocks = vec![0; CHUNK_TOTAL_BLOCKS];
+        self.shape_terrain(chunk_x, chunk_z, &mut blocks, &climate);
+        self.replace_surface(chunk_x, chunk_z, &mut blocks, &climate, &mut random);
+        self.caves.carve(chunk_x, chunk_z, &mut blocks);
+
+        BlockArray::from_blocks(blocks)
+    }
+
+    fn populate(&self, area: &mut PopulationArea) {
+        let x = area.chunk_x() * 16;
+        let z = area.chunk_z() * 16;
+        let biome = self.biomes.climate(x + 16, z + 16, 1).biomes[0];
+
+        let mut random = JavaRandom::new(self.seed);
+        let x_multiplier = random.next_long() / 2 * 2 + 1;
+        let z_multiplier = random.next_long() / 2 * 2 + 1;
+        let seed = (area.chunk_x() as i64).wrapping_mul(x_multiplier)
+            .wrapping_add((area.chunk_z() as i64).wrapping_mul(z_multiplier));
+        random.set_seed(seed ^ self.seed);
+
+        for (block, size, count, max_y) in VEINS {
+            let vein = Minable::new(block, size);
+            for _ in 0..count {
+                let vein_x = x + random.next_int(16);
+                let vein_y = random.next_int(max_y);
+                let vein_z = z + random.next_int(16);
+                vein.generate(area, &mut random, vein_x, vein_y, vein_z);
+            }
+        }
+
+        let forest = self.forest.sample_2d(x as f64 * 0.5, z as f64 * 0.5);
+        let forest = ((forest / 8.0 + random.next_double() * 4.0 + 4.0) / 3.0) as i32;
+        let mut trees = 0;
+        if random.next_int(10) == 0 {
+            trees += 1;
+        }
+        match biome {
+            Biome::Forest | Biome::Rainforest | Biome::Taiga => trees += forest + 5,
+            Biome::SeasonalForest => trees += forest + 2,
+            Biome::Desert | Biome::Tundra | Biome::Plains => trees -= 20,
+            _ => (),
+        }
+
+        for _ in 0..trees {
+            let tree_x = x + random.next_int(16) + 8;
+            let tree_z = z + random.next_int(16) + 8;
+            let big_chance = match biome {
+                Biome::Forest | Biome::Rainforest => 3,
+                _ => 10,
+            };
+
+            let tree_y = area.height(tree_x, tree_z);
+            if random.next_int(big_chance) == 0 {
+                // Big trees grow from a generator of their own
+                let mut tree_random = JavaRandom::new(random.next_long());
+                Tree::new_tall().generate(area, &mut tree_random, tree_x, tree_y, tree_z);
+            } else {
+                Tree::new().generate(area, &mut random, tree_x, tree_y, tree_z);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Generate the chunks around a chunk and populate it, returning its
+    /// blocks and those of its neighbours.
+    fn generate_and_populate(generator: &BetaGenerator, x: i32, z: i32) -> Vec<Vec<u8>> {
+        let mut chunks = [(x, z), (x + 1, z), (x, z + 1), (x + 1, z + 1)]
+            .map(|(x, z)| generator.generate(x, z));
+        generator.populate(&mut PopulationArea::new(x, z, chunks.each_mut()));
+
+        chunks.iter().map(|c| c.raw_blocks().to_vec()).collect()
+    }
+
+    #[test]
+    fn same_seed_same_chunks() {
+        for (seed, x, z) in [(0, 0, 0), (-4172144997902289642, -3, 7), (8675309, 100, -100)] {
+            let first = generate_and_populate(&BetaGenerator::new(seed), x, z);
+            let second = generate_and_populate(&BetaGenerator::new(seed), x, z);
+            assert!(first == second, "chunk {x}, {z} of seed {seed} differs");
+        }
+    }
+
+    #[test]
+    fn different_seeds_different_chunks() {
+        let first = generate_and_populate(&BetaGenerator::new(1), 0, 0);
+        let second = generate_and_populate(&BetaGenerator::new(2), 0, 0);
+        assert!(first != second);
+    }
+}
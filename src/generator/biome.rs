@@ -0,0 +1,125 @@
+use crate::blocks_items::Block;
+
+use super::{noise::SimplexOctaveNoise, random::JavaRandom};
+
+/// The climate of an area, picked from its temperature and humidity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Biome {
+    Rainforest,
+    Swampland,
+    SeasonalForest,
+    Forest,
+    Savanna,
+    Shrubland,
+    Taiga,
+    Desert,
+    Plains,
+    Tundra,
+}
+
+impl Biome {
+    /// The biome for a temperature and humidity, both from 0 to 1.
+    pub fn from_climate(temperature: f64, humidity: f64) -> Self {
+        // The original looks biomes up in a 64x64 table
+        let temperature = (temperature * 63.0) as i32 as f32 / 63.0;
+        let humidity = (humidity * 63.0) as i32 as f32 / 63.0 * temperature;
+
+        if temperature < 0.1 {
+            Biome::Tundra
+        } else if humidity < 0.2 {
+            if temperature < 0.5 {
+                Biome::Tundra
+            } else if temperature < 0.95 {
+                Biome::Savanna
+            } else {
+                Biome::Desert
+            }
+        } else if humidity > 0.5 && temperature < 0.7 {
+            Biome::Swampland
+        } else if temperature < 0.5 {
+            Biome::Taiga
+        } else if temperature < 0.97 {
+            if humidity < 0.35 {
+                Biome::Shrubland
+            } else {
+                Biome::Forest
+            }
+        } else if humidity < 0.45 {
+            Biome::Plains
+        } else if humidity < 0.9 {
+            Biome::SeasonalForest
+        } else {
+            Biome::Rainforest
+        }
+    }
+
+    /// The block on the surface of the ground.
+    pub fn top_block(&self) -> Block {
+        match self {
+            Biome::Desert => Block::Sand,
+            _ => Block::Grass,
+        }
+    }
+
+    /// The block under the surface, down to the stone.
+    pub fn filler_block(&self) -> Block {
+        match self {
+            Biome::Desert => Block::Sand,
+            _ => Block::Dirt,
+        }
+    }
+}
+
+/// The climate of a square area of the world, ordered by x, then z.
+#[derive(Debug, Clone)]
+pub struct Climate {
+    pub temperature: Vec<f64>,
+    pub humidity: Vec<f64>,
+    pub biomes: Vec<Biome>,
+}
+
+/// Picks the temperature, humidity and biome of every column of the world.
+#[derive(Debug, Clone)]
+pub struct BiomeSource {
+    temperature: SimplexOctaveNoise,
+    humidity: SimplexOctaveNoise,
+    variation: SimplexOctaveNoise,
+}
+
+impl BiomeSource {
+    pub fn new(seed: i64) -> Self {
+        Self {
+            temperature: SimplexOctaveNoise::new(&mut JavaRandom::new(seed.wrapping_mul(9871)), 4),
+            humidity: SimplexOctaveNoise::new(&mut JavaRandom::new(seed.wrapping_mul(39811)), 4),
+            variation: SimplexOctaveNoise::new(&mut JavaRandom::new(seed.wrapping_mul(543321)), 2),
+        }
+    }
+
+    /// The climate of the `size` by `size` area with its lowest corner at
+    /// the given block position.
+    pub fn climate(&self, x: i32, z: i32, size: usize) -> Climate {
+        let position = (x as f64, z as f64);
+        let temperature = self.temperature.grid(position, (size, size), (0.025000000372529, 0.025000000372529), 0.25);
+        let humidity = self.humidity.grid(position, (size, size), (0.05000000074505806, 0.05000000074505806), 1.0 / 3.0);
+        let variation = self.variation.grid(position, (size, size), (0.25, 0.25), 0.5882352941176471);
+
+        let mut climate = Climate {
+            temperature,
+            humidity,
+            biomes: Vec::with_capacity(size * size),
+        };
+        for i in 0..size * size {
+            let variation = variation[i] * 1.1 + 0.5;
+
+            let temperature = (climate.temperature[i] * 0.15 + 0.7) * (1.0 - 0.01) + variation * 0.01;
+            let temperature = (1.0 - (1.0 - temperature) * (1.0 - temperature)).clamp(0.0, 1.0);
+            let humidity = ((climate.humidity[i] * 0.15 + 0.5) * (1.0 - 0.002) + variation * 0.002).clamp(0.0, 1.0);
+
+            climate.temperature[i] = temperature;
+            climate.humidity[i] = humidity;
+            climate.biomes.push(Biome::from_climate(temperature, humidity));
+        }
+
+        climate
+    }
+}
@@ -0,0 +1,272 @@
+use crate::{blocks_items::Block, chunk::CHUNK_HEIGHT_Y};
+
+use super::random::{floor, table_cos, table_sin, JavaRandom, HALF_PI, PI};
+
+/// How many chunks away a cave can start and still reach a chunk.
+const RANGE: i32 = 8;
+
+/// Carves caves through the terrain of a chunk.
+///
+/// Every chunk in [`RANGE`] may start caves, which are followed through the
+/// chunk being generated so that caves line up across chunk borders.
+#[derive(Debug, Clone)]
+pub struct Caves {
+    seed: i64,
+}
+
+impl Caves {
+    pub fn new(seed: i64) -> Self {
+        Self { seed }
+    }
+
+    /// Carve the caves which pass through a chunk out of its blocks.
+    pub fn carve(&self, chunk_x: i32, chunk_z: i32, blocks: &mut [u8]) {
+        let mut random = JavaRandom::new(self.seed);
+        let x_multiplier = random.next_long() / 2 * 2 + 1;
+        let z_multiplier = random.next_long() / 2 * 2 + 1;
+
+        for start_x in chunk_x - RANGE..=chunk_x + RANGE {
+            for start_z in chunk_z - RANGE..=chunk_z + RANGE {
+                let seed = (start_x as i64).wrapping_mul(x_multiplier)
+                    .wrapping_add((start_z as i64).wrapping_mul(z_multiplier));
+                random.set_seed(seed ^ self.seed);
+                self.start_caves(&mut random, start_x, start_z, chunk_x, chunk_z, blocks);
+            }
+        }
+    }
+
+    /// Start the caves of one chunk, carving the parts of them which are in
+    /// the chunk being generated.
+    fn start_caves(
+        &self,
+        random: &mut JavaRandom,
+        start_x: i32,
+        start_z: i32,
+        chunk_x: i32,
+        chunk_z: i32,
+        blocks: &mut [u8],
+    ) {
+        let bound = random.next_int(40) + 1;
+        let bound = random.next_int(bound) + 1;
+        let mut count = random.next_int(bound);
+        if random.next_int(15) != 0 {
+            count = 0;
+        }
+
+        for _ in 0..count {
+            let x = (start_x * 16 + random.next_int(16)) as f64;
+            let bound = random.next_int(120) + 8;
+            let y = random.next_int(bound) as f64;
+            let z = (start_z * 16 + random.next_int(16)) as f64;
+
+            let mut tunnels = 1;
+            if random.next_int(4) == 0 {
+                let width = 1.0 + random.next_float() * 6.0;
+                let cave = Tunnel::new((x, y, z), width, 0.0, 0.0, 0.5);
+                cave.carve(random, chunk_x, chunk_z, blocks, -1, -1);
+                tunnels += random.next_int(4);
+            }
+
+            for _ in 0..tunnels {
+                let yaw = random.next_float() * PI * 2.0;
+                let pitch = (random.next_float() - 0.5) * 2.0 / 8.0;
+                let width = random.next_float() * 2.0 + random.next_float();
+                let tunnel = Tunnel::new((x, y, z), width, yaw, pitch, 1.0);
+                tunnel.carve(random, chunk_x, chunk_z, blocks, 0, 0);
+            }
+        }
+    }
+}
+
+/// A single winding tunnel, or a round room when it is carved from the
+/// middle.
+#[derive(Debug, Clone, Copy)]
+struct Tunnel {
+    x: f64,
+    y: f64,
+    z: f64,
+    width: f32,
+    yaw: f32,
+    pitch: f32,
+    /// How tall the tunnel is compared to its width
+    vertical_scale: f64,
+}
+
+impl Tunnel {
+    fn new((x, y, z): (f64, f64, f64), width: f32, yaw: f32, pitch: f32, vertical_scale: f64) -> Self {
+        Self {
+            x,
+            y,
+            z,
+            width,
+            yaw,
+            pitch,
+            vertical_scale,
+        }
+    }
+
+    /// Follow the tunnel from `step` to `length`, carving it where it passes
+    /// through the chunk. A `length` of 0 picks a random length, and a
+    /// `step` of -1 carves a room from the middle of the tunnel.
+    fn carve(
+        mut self,
+        random: &mut JavaRandom,
+        chunk_x: i32,
+        chunk_z: i32,
+        blocks: &mut [u8],
+        mut step: i32,
+        mut length: i32,
+    ) {
+        let centre_x = (chunk_x * 16 + 8) as f64;
+        let centre_z = (chunk_z * 16 + 8) as f64;
+        let mut yaw_change = 0.0f32;
+        let mut pitch_change = 0.0f32;
+
+        let mut random_tunnel = JavaRandom::new(random.next_long());
+        if length <= 0 {
+            let max_length = RANGE * 16 - 16;
+            length = max_length - random_tunnel.next_int(max_length / 4);
+        }
+
+        let mut room = false;
+        if step == -1 {
+            step = length / 2;
+            room = true;
+        }
+
+        let branch_step = random_tunnel.next_int(length / 2) + length / 4;
+        let steep = random_tunnel.next_int(6) == 0;
+
+        while step < length {
+            let radius = 1.5 + (table_sin(step as f32 * PI / length as f32) * self.width * 1.0) as f64;
+            let vertical_radius = radius * self.vertical_scale;
+
+            let horizontal = table_cos(self.pitch);
+            self.x += (table_cos(self.yaw) * horizontal) as f64;
+            self.y += table_sin(self.pitch) as f64;
+            self.z += (table_sin(self.yaw) * horizontal) as f64;
+
+            self.pitch *= if steep { 0.92 } else { 0.7 };
+            self.pitch += pitch_change * 0.1;
+            self.yaw += yaw_change * 0.1;
+            pitch_change *= 0.9;
+            yaw_change *= 0.75;
+            pitch_change += (random_tunnel.next_float() - random_tunnel.next_float()) * random_tunnel.next_float() * 2.0;
+            yaw_change += (random_tunnel.next_float() - random_tunnel.next_float()) * random_tunnel.next_float() * 4.0;
+
+            if !room && step == branch_step && self.width > 1.0 {
+                for turn in [-HALF_PI, HALF_PI] {
+                    let width = random_tunnel.next_float() * 0.5 + 0.5;
+                    let branch = Tunnel::new((self.x, self.y, self.z), width, self.yaw + turn, self.pitch / 3.0, 1.0);
+                    branch.carve(random, chunk_x, chunk_z, blocks, step, length);
+                }
+                return
+            }
+
+            if !room && random_tunnel.next_int(4) == 0 {
+                step += 1;
+                continue
+            }
+
+            // Stop once the tunnel can't come back to the chunk
+            let dx = self.x - centre_x;
+            let dz = self.z - centre_z;
+            let remaining = (length - step) as f64;
+            let reach = (self.width + 2.0 + 16.0) as f64;
+            if dx * dx + dz * dz - remaining * remaining > reach * reach {
+                return
+            }
+
+            if self.x < centre_x - 16.0 - radius * 2.0
+                || self.z < centre_z - 16.0 - radius * 2.0
+                || self.x > centre_x + 16.0 + radius * 2.0
+                || self.z > centre_z + 16.0 + radius * 2.0
+            {
+                step += 1;
+                continue
+            }
+
+            // A room which reaches water moves on instead of stopping
+            if !self.carve_step(chunk_x, chunk_z, blocks, radius, vertical_radius) {
+                step += 1;
+                continue
+            }
+
+            if room {
+                break
+            }
+            step += 1;
+        }
+    }
+
+    /// Carve the ellipsoid around the current position of the tunnel,
+    /// returning `false` if it was next to water and left alone.
+    fn carve_step(&self, chunk_x: i32, chunk_z: i32, blocks: &mut [u8], radius: f64, vertical_radius: f64) -> bool {
+        let min_x = (floor(self.x - radius) - chunk_x * 16 - 1).max(0);
+        let max_x = (floor(self.x + radius) - chunk_x * 16 + 1).min(16);
+        let min_y = (floor(self.y - vertical_radius) - 1).max(1);
+        let max_y = (floor(self.y + vertical_radius) + 1).min(120);
+        let min_z = (floor(self.z - radius) - chunk_z * 16 - 1).max(0);
+        let max_z = (floor(self.z + radius) - chunk_z * 16 + 1).min(16);
+
+        let index = |x: i32, y: i32, z: i32| ((x * 16 + z) * CHUNK_HEIGHT_Y as i32 + y) as usize;
+        let is_water = |block: u8| block == Block::WaterStill as u8 || block == Block::WaterMoving as u8;
+
+        // Caves don't cut into water, which only checks the outside of the
+        // area like the original
+        for x in min_x..max_x {
+            for z in min_z..max_z {
+                let mut y = max_y + 1;
+                while y >= min_y - 1 {
+                    if (0..CHUNK_HEIGHT_Y as i32).contains(&y) {
+                        if is_water(blocks[index(x, y, z)]) {
+                            return false
+                        }
+                        if y != min_y - 1 && x != min_x && x != max_x - 1 && z != min_z && z != max_z - 1 {
+                            y = min_y;
+                        }
+                    }
+                    y -= 1;
+                }
+            }
+        }
+
+        for x in min_x..max_x {
+            let nx = ((x + chunk_x * 16) as f64 + 0.5 - self.x) / radius;
+            for z in min_z..max_z {
+                let nz = ((z + chunk_z * 16) as f64 + 0.5 - self.z) / radius;
+                if nx * nx + nz * nz >= 1.0 {
+                    continue
+                }
+
+                // The original checks one block above the position it
+                // measures, which is kept so caves come out the same
+                let mut i = index(x, max_y, z);
+                let mut grass = false;
+                for y in (min_y..max_y).rev() {
+                    let ny = (y as f64 + 0.5 - self.y) / vertical_radius;
+                    if ny > -0.7 && nx * nx + ny * ny + nz * nz < 1.0 {
+                        let block = blocks[i];
+                        if block == Block::Grass as u8 {
+                            grass = true;
+                        }
+
+                        if block == Block::Stone as u8 || block == Block::Dirt as u8 || block == Block::Grass as u8 {
+                            if y < 10 {
+                                blocks[i] = Block::LavaMoving as u8;
+                            } else {
+                                blocks[i] = Block::Air as u8;
+                                if grass && blocks[i - 1] == Block::Dirt as u8 {
+                                    blocks[i - 1] = Block::Grass as u8;
+                                }
+                            }
+                        }
+                    }
+                    i -= 1;
+                }
+            }
+        }
+
+        true
+    }
+}
@@ -0,0 +1,149 @@
+use num_traits::FromPrimitive;
+
+use crate::blocks_items::Block;
+
+use super::{
+    random::{floor, table_cos, table_sin, JavaRandom, PI},
+    PopulationArea,
+};
+
+/// A vein of ore, or of dirt or gravel, which replaces stone.
+#[derive(Debug, Clone, Copy)]
+pub struct Minable {
+    block: Block,
+    size: i32,
+}
+
+impl Minable {
+    pub fn new(block: Block, size: i32) -> Self {
+        Self { block, size }
+    }
+
+    /// Place the vein starting 8 blocks into the chunk from the position.
+    pub fn generate(&self, area: &mut PopulationArea, random: &mut JavaRandom, x: i32, y: i32, z: i32) {
+        let size = self.size as f32;
+        let angle = random.next_float() * PI;
+        let start_x = ((x + 8) as f32 + table_sin(angle) * size / 8.0) as f64;
+        let end_x = ((x + 8) as f32 - table_sin(angle) * size / 8.0) as f64;
+        let start_z = ((z + 8) as f32 + table_cos(angle) * size / 8.0) as f64;
+        let end_z = ((z + 8) as f32 - table_cos(angle) * size / 8.0) as f64;
+        let start_y = (y + random.next_int(3) - 2) as f64;
+        let end_y = (y + random.next_int(3) - 2) as f64;
+
+        let size = self.size as f64;
+        for i in 0..=self.size {
+            let centre_x = start_x + (end_x - start_x) * i as f64 / size;
+            let centre_y = start_y + (end_y - start_y) * i as f64 / size;
+            let centre_z = start_z + (end_z - start_z) * i as f64 / size;
+
+            let scale = random.next_double() * size / 16.0;
+            let thickness = (table_sin(i as f32 * PI / self.size as f32) + 1.0) as f64 * scale + 1.0;
+            let radius = thickness / 2.0;
+
+            for bx in floor(centre_x - radius)..=floor(centre_x + radius) {
+                let dx = (bx as f64 + 0.5 - centre_x) / radius;
+                if dx * dx >= 1.0 {
+                    continue
+                }
+
+                for by in floor(centre_y - radius)..=floor(centre_y + radius) {
+                    let dy = (by as f64 + 0.5 - centre_y) / radius;
+                    if dx * dx + dy * dy >= 1.0 {
+                        continue
+                    }
+
+                    for bz in floor(centre_z - radius)..=floor(centre_z + radius) {
+                        let dz = (bz as f64 + 0.5 - centre_z) / radius;
+                        if dx * dx + dy * dy + dz * dz < 1.0 && area.block(bx, by, bz) == Block::Stone as u8 {
+                            area.set_block(bx, by, bz, self.block as u8);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An oak tree with a round top of leaves.
+#[derive(Debug, Clone, Copy)]
+pub struct Tree {
+    /// The shortest trunk the tree can have
+    min_height: i32,
+}
+
+impl Tree {
+    pub fn new() -> Self {
+        Self { min_height: 4 }
+    }
+
+    /// A taller tree, standing in for the branching big trees of the
+    /// original.
+    pub fn new_tall() -> Self {
+        Self { min_height: 7 }
+    }
+
+    /// Grow the tree with its trunk starting at the position, returning
+    /// `false` if there isn't room for it.
+    pub fn generate(&self, area: &mut PopulationArea, random: &mut JavaRandom, x: i32, y: i32, z: i32) -> bool {
+        let height = random.next_int(3) + self.min_height;
+        if y < 1 || y + height + 1 > 128 {
+            return false
+        }
+
+        // Everything the tree will take up has to be empty
+        for by in y..=y + 1 + height {
+            let radius = if by == y {
+                0
+            } else if by >= y + 1 + height - 2 {
+                2
+            } else {
+                1
+            };
+
+            for bx in x - radius..=x + radius {
+                for bz in z - radius..=z + radius {
+                    let block = area.block(bx, by, bz);
+                    if block != Block::Air as u8 && block != Block::Leaves as u8 {
+                        return false
+                    }
+                }
+            }
+        }
+
+        let ground = area.block(x, y - 1, z);
+        if ground != Block::Grass as u8 && ground != Block::Dirt as u8 || y >= 128 - height - 1 {
+            return false
+        }
+        area.set_block(x, y - 1, z, Block::Dirt as u8);
+
+        for by in y - 3 + height..=y + height {
+            let layer = by - (y + height);
+            let radius = 1 - layer / 2;
+            for bx in x - radius..=x + radius {
+                for bz in z - radius..=z + radius {
+                    // The corners are left out at random, except on the top
+                    let corner = (bx - x).abs() == radius && (bz - z).abs() == radius;
+                    let solid = Block::from_u8(area.block(bx, by, bz)).is_some_and(|b| b.light_opacity() == 15);
+                    if (!corner || random.next_int(2) != 0 && layer != 0) && !solid {
+                        area.set_block(bx, by, bz, Block::Leaves as u8);
+                    }
+                }
+            }
+        }
+
+        for by in y..y + height {
+            let block = area.block(x, by, z);
+            if block == Block::Air as u8 || block == Block::Leaves as u8 {
+                area.set_block(x, by, z, Block::Wood as u8);
+            }
+        }
+
+        true
+    }
+}
+
+impl Default for Tree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,145 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+use num_traits::FromPrimitive;
+
+use crate::{
+    blocks_items::Block,
+    chunk::{BlockArray, CHUNK_HEIGHT_Y, CHUNK_TOTAL_BLOCKS, CHUNK_WIDTH_X, CHUNK_WIDTH_Z},
+};
+
+use super::WorldGenerator;
+
+/// Grass on six layers of dirt on bedrock.
+pub const DEFAULT_PRESET: &str = "7,6*3,2";
+
+/// Generates a flat world from a list of layers.
+///
+/// The layers are given by a preset like `7,6*3,2`, listing block IDs from
+/// the bottom of the world up. A layer written as `count*block` is repeated
+/// `count` times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatGenerator {
+    /// The block of each layer, starting at Y 0
+    layers: Vec<Block>,
+}
+
+impl FlatGenerator {
+    pub fn new(layers: Vec<Block>) -> Self {
+        Self { layers }
+    }
+
+    /// The blocks of each layer, starting at Y 0.
+    pub fn layers(&self) -> &[Block] {
+        &self.layers
+    }
+}
+
+impl Default for FlatGenerator {
+    fn default() -> Self {
+        DEFAULT_PRESET.parse().unwrap()
+    }
+}
+
+impl WorldGenerator for FlatGenerator {
+    fn generate(&self, _chunk_x: i32, _chunk_z: i32) -> BlockArray {
+        let mut blocks = vec![Block::Air as u8; CHUNK_TOTAL_BLOCKS];
+        for x in 0..CHUNK_WIDTH_X {
+            for z in 0..CHUNK_WIDTH_Z {
+                let column = (x * CHUNK_WIDTH_Z + z) * CHUNK_HEIGHT_Y;
+                for (y, block) in self.layers.iter().enumerate() {
+                    blocks[column + y] = *block as u8;
+                }
+            }
+        }
+
+        BlockArray::from_blocks(blocks)
+    }
+}
+
+impl FromStr for FlatGenerator {
+    type Err = PresetError;
+
+    fn from_str(preset: &str) -> Result<Self, Self::Err> {
+        let mut layers = Vec::new();
+        for layer in preset.split(',').map(str::trim) {
+            let (count, id) = match layer.split_once('*') {
+                Some((count, id)) => (count.trim(), id.trim()),
+                None => ("1", layer),
+            };
+
+            let count: usize = count.parse().map_err(|_| PresetError::BadLayer(layer.to_string()))?;
+            let id: u8 = id.parse().map_err(|_| PresetError::BadLayer(layer.to_string()))?;
+            let block = Block::from_u8(id).ok_or(PresetError::UnknownBlock(id))?;
+
+            if layers.len() + count > CHUNK_HEIGHT_Y {
+                return Err(PresetError::TooTall)
+            }
+            layers.extend(std::iter::repeat_n(block, count));
+        }
+
+        // Players would have nothing to stand on
+        if !layers.iter().any(Block::is_solid) {
+            return Err(PresetError::NoSolidLayer)
+        }
+
+        Ok(Self::new(layers))
+    }
+}
+
+/// The reason a superflat preset could not be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresetError {
+    /// A layer isn't a block ID, optionally with a count
+    BadLayer(String),
+
+    /// A layer uses a block ID which doesn't exist
+    UnknownBlock(u8),
+
+    /// The layers don't fit in the height of the world
+    TooTall,
+
+    /// None of the layers are solid, so the world would be empty
+    NoSolidLayer,
+}
+
+impl Display for PresetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadLayer(layer) => write!(f, "Bad layer \"{layer}\", expected a block ID or count*ID"),
+            Self::UnknownBlock(id) => write!(f, "Unknown block ID {id}"),
+            Self::TooTall => write!(f, "The layers are taller than {CHUNK_HEIGHT_Y} blocks"),
+            Self::NoSolidLayer => write!(f, "None of the layers are solid"),
+        }
+    }
+}
+
+impl Error for PresetError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_default_preset() {
+        let generator: FlatGenerator = DEFAULT_PRESET.parse().unwrap();
+        let mut expected = vec![Block::Bedrock];
+        expected.extend([Block::Dirt; 6]);
+        expected.push(Block::Grass);
+
+        assert_eq!(generator.layers(), expected);
+    }
+
+    #[test]
+    fn rejects_presets_without_solid_layers() {
+        for preset in ["0*1", "1*0", "0,0", "8,9"] {
+            assert_eq!(preset.parse::<FlatGenerator>(), Err(PresetError::NoSolidLayer), "{preset}");
+        }
+    }
+
+    #[test]
+    fn rejects_bad_layers() {
+        assert_eq!("x".parse::<FlatGenerator>(), Err(PresetError::BadLayer("x".to_string())));
+        assert_eq!("7,200".parse::<FlatGenerator>(), Err(PresetError::UnknownBlock(200)));
+        assert_eq!("129*1".parse::<FlatGenerator>(), Err(PresetError::TooTall));
+    }
+}
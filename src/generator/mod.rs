@@ -0,0 +1,99 @@
+use crate::chunk::{BlockArray, CHUNK_HEIGHT_Y, CHUNK_WIDTH_X, CHUNK_WIDTH_Z};
+
+pub mod beta;
+pub mod biome;
+pub mod caves;
+pub mod features;
+pub mod flat;
+pub mod noise;
+pub mod random;
+
+/// Creates the chunks of a world which haven't been saved yet.
+///
+/// Generation happens in two steps, like the original. [`generate`] creates
+/// the terrain of a single chunk, and once the chunks next to it exist
+/// [`populate`] adds features like ores and trees which may cross into
+/// them.
+///
+/// [`generate`]: WorldGenerator::generate
+/// [`populate`]: WorldGenerator::populate
+pub trait WorldGenerator: Send {
+    /// Generate the terrain of the chunk at the given chunk coordinates.
+    fn generate(&self, chunk_x: i32, chunk_z: i32) -> BlockArray;
+
+    /// Add features to the chunk at the lowest corner of the area.
+    fn populate(&self, _area: &mut PopulationArea) {}
+}
+
+/// The 2x2 chunks a chunk is populated in, addressed by block positions in
+/// the world.
+///
+/// Features are placed 8 blocks into the chunk so they can spread into its
+/// neighbours without reaching past them.
+pub struct PopulationArea<'a> {
+    chunk_x: i32,
+    chunk_z: i32,
+    /// The chunks at `(x, z)`, `(x + 1, z)`, `(x, z + 1)` and `(x + 1, z + 1)`
+    chunks: [&'a mut BlockArray; 4],
+}
+
+impl<'a> PopulationArea<'a> {
+    pub fn new(chunk_x: i32, chunk_z: i32, chunks: [&'a mut BlockArray; 4]) -> Self {
+        Self {
+            chunk_x,
+            chunk_z,
+            chunks,
+        }
+    }
+
+    /// The X coordinate of the chunk being populated.
+    pub fn chunk_x(&self) -> i32 {
+        self.chunk_x
+    }
+
+    /// The Z coordinate of the chunk being populated.
+    pub fn chunk_z(&self) -> i32 {
+        self.chunk_z
+    }
+
+    /// The chunk a position is in and the position within it.
+    fn locate(&self, x: i32, y: i32, z: i32) -> Option<(usize, usize, usize, usize)> {
+        let local_x = x - self.chunk_x * CHUNK_WIDTH_X as i32;
+        let local_z = z - self.chunk_z * CHUNK_WIDTH_Z as i32;
+        let inside = (0..CHUNK_WIDTH_X as i32 * 2).contains(&local_x)
+            && (0..CHUNK_HEIGHT_Y as i32).contains(&y)
+            && (0..CHUNK_WIDTH_Z as i32 * 2).contains(&local_z);
+        if !inside {
+            return None
+        }
+
+        let (local_x, local_z) = (local_x as usize, local_z as usize);
+        let chunk = local_x / CHUNK_WIDTH_X + local_z / CHUNK_WIDTH_Z * 2;
+        Some((chunk, local_x % CHUNK_WIDTH_X, y as usize, local_z % CHUNK_WIDTH_Z))
+    }
+
+    /// The block at a position, or air if it is outside the area.
+    pub fn block(&self, x: i32, y: i32, z: i32) -> u8 {
+        match self.locate(x, y, z) {
+            Some((chunk, x, y, z)) => self.chunks[chunk].block(x, y, z),
+            None => 0,
+        }
+    }
+
+    /// Change a block, doing nothing if it is outside the area.
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: u8) {
+        if let Some((chunk, x, y, z)) = self.locate(x, y, z) {
+            self.chunks[chunk].set_block(x, y, z, block);
+            self.chunks[chunk].set_metadata(x, y, z, 0);
+        }
+    }
+
+    /// The lowest Y position above every block which blocks light, or 0 if
+    /// the column is outside the area.
+    pub fn height(&self, x: i32, z: i32) -> i32 {
+        match self.locate(x, 0, z) {
+            Some((chunk, x, _, z)) => self.chunks[chunk].height(x, z) as i32,
+            None => 0,
+        }
+    }
+}
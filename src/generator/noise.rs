@@ -0,0 +1,357 @@
+//! The noise functions of the original world generator.
+//!
+//! These are ports rather than textbook implementations, so that they give
+//! exactly the same values from the same [`JavaRandom`].
+
+use super::random::JavaRandom;
+
+/// Build the shuffled permutation table shared by both kinds of noise.
+fn permutations(random: &mut JavaRandom) -> [usize; 512] {
+    let mut permutations = [0; 512];
+    for (i, p) in permutations.iter_mut().take(256).enumerate() {
+        *p = i;
+    }
+
+    for i in 0..256 {
+        let j = random.next_int(256 - i as i32) as usize + i;
+        permutations.swap(i, j);
+        permutations[i + 256] = permutations[i];
+    }
+
+    permutations
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn grad(hash: usize, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 { y } else if h == 12 || h == 14 { x } else { z };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+fn grad_2d(hash: usize, x: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = (1 - ((h & 8) >> 3)) as f64 * x;
+    let v = if h < 4 { 0.0 } else if h == 12 || h == 14 { x } else { z };
+
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+/// Split a coordinate into its lattice cell and the position within it.
+fn cell(value: f64) -> (usize, f64) {
+    let floor = value.floor();
+    ((floor as i64 & 255) as usize, value - floor)
+}
+
+/// Improved Perlin noise.
+#[derive(Debug, Clone)]
+pub struct PerlinNoise {
+    permutations: [usize; 512],
+    x_offset: f64,
+    y_offset: f64,
+    z_offset: f64,
+}
+
+impl PerlinNoise {
+    pub fn new(random: &mut JavaRandom) -> Self {
+        let x_offset = random.next_double() * 256.0;
+        let y_offset = random.next_double() * 256.0;
+        let z_offset = random.next_double() * 256.0;
+
+        Self {
+            permutations: permutations(random),
+            x_offset,
+            y_offset,
+            z_offset,
+        }
+    }
+
+    /// The noise at a single point.
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        let p = &self.permutations;
+        let (xi, x) = cell(x + self.x_offset);
+        let (yi, y) = cell(y + self.y_offset);
+        let (zi, z) = cell(z + self.z_offset);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let a = p[xi] + yi;
+        let aa = p[a] + zi;
+        let ab = p[a + 1] + zi;
+        let b = p[xi + 1] + yi;
+        let ba = p[b] + zi;
+        let bb = p[b + 1] + zi;
+
+        lerp(w,
+            lerp(v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                lerp(u, grad(p[ab], x, y - 1.0, z), grad(p[bb], x - 1.0, y - 1.0, z)),
+            ),
+            lerp(v,
+                lerp(u, grad(p[aa + 1], x, y, z - 1.0), grad(p[ba + 1], x - 1.0, y, z - 1.0)),
+                lerp(u, grad(p[ab + 1], x, y - 1.0, z - 1.0), grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0)),
+            ),
+        )
+    }
+
+    /// Add the noise over a grid of points to `output`, which is ordered by
+    /// x, then z, then y.
+    ///
+    /// A grid with a height of one is sampled as 2D noise.
+    #[allow(clippy::too_many_arguments)]
+    fn add_grid(
+        &self,
+        output: &mut [f64],
+        (x, y, z): (f64, f64, f64),
+        (size_x, size_y, size_z): (usize, usize, usize),
+        (scale_x, scale_y, scale_z): (f64, f64, f64),
+        amplitude: f64,
+    ) {
+        let p = &self.permutations;
+        let inverse = 1.0 / amplitude;
+        let mut index = 0;
+
+        if size_y == 1 {
+            for ix in 0..size_x {
+                let (xi, x) = cell((x + ix as f64) * scale_x + self.x_offset);
+                let u = fade(x);
+                for iz in 0..size_z {
+                    let (zi, z) = cell((z + iz as f64) * scale_z + self.z_offset);
+                    let w = fade(z);
+
+                    let a = p[xi];
+                    let aa = p[a] + zi;
+                    let b = p[xi + 1];
+                    let ba = p[b] + zi;
+                    let lower = lerp(u, grad_2d(p[aa], x, z), grad(p[ba], x - 1.0, 0.0, z));
+                    let upper = lerp(u, grad(p[aa + 1], x, 0.0, z - 1.0), grad(p[ba + 1], x - 1.0, 0.0, z - 1.0));
+
+                    output[index] += lerp(w, lower, upper) * inverse;
+                    index += 1;
+                }
+            }
+            return
+        }
+
+        let mut last_yi = None;
+        let (mut x1, mut x2, mut x3, mut x4) = (0.0, 0.0, 0.0, 0.0);
+        for ix in 0..size_x {
+            let (xi, x) = cell((x + ix as f64) * scale_x + self.x_offset);
+            let u = fade(x);
+            for iz in 0..size_z {
+                let (zi, z) = cell((z + iz as f64) * scale_z + self.z_offset);
+                let w = fade(z);
+                for iy in 0..size_y {
+                    let (yi, y) = cell((y + iy as f64) * scale_y + self.y_offset);
+                    let v = fade(y);
+
+                    // Neighbouring rows often share a cell, so the x
+                    // interpolation is only redone when it changes
+                    if iy == 0 || last_yi != Some(yi) {
+                        last_yi = Some(yi);
+                        let a = p[xi] + yi;
+                        let aa = p[a] + zi;
+                        let ab = p[a + 1] + zi;
+                        let b = p[xi + 1] + yi;
+                        let ba = p[b] + zi;
+                        let bb = p[b + 1] + zi;
+                        x1 = lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z));
+                        x2 = lerp(u, grad(p[ab], x, y - 1.0, z), grad(p[bb], x - 1.0, y - 1.0, z));
+                        x3 = lerp(u, grad(p[aa + 1], x, y, z - 1.0), grad(p[ba + 1], x - 1.0, y, z - 1.0));
+                        x4 = lerp(u, grad(p[ab + 1], x, y - 1.0, z - 1.0), grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0));
+                    }
+
+                    output[index] += lerp(w, lerp(v, x1, x2), lerp(v, x3, x4)) * inverse;
+                    index += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Several octaves of [`PerlinNoise`], each at twice the frequency and half
+/// the amplitude of the last.
+#[derive(Debug, Clone)]
+pub struct OctaveNoise {
+    octaves: Vec<PerlinNoise>,
+}
+
+impl OctaveNoise {
+    pub fn new(random: &mut JavaRandom, octaves: usize) -> Self {
+        Self {
+            octaves: (0..octaves).map(|_| PerlinNoise::new(random)).collect(),
+        }
+    }
+
+    /// The noise at a single point on the plane `y = 0`.
+    pub fn sample_2d(&self, x: f64, z: f64) -> f64 {
+        let mut frequency = 1.0;
+        let mut value = 0.0;
+        for octave in &self.octaves {
+            value += octave.sample(x * frequency, z * frequency, 0.0) / frequency;
+            frequency /= 2.0;
+        }
+
+        value
+    }
+
+    /// The noise over a grid of points, ordered by x, then z, then y.
+    pub fn grid(
+        &self,
+        position: (f64, f64, f64),
+        size: (usize, usize, usize),
+        scale: (f64, f64, f64),
+    ) -> Vec<f64> {
+        let mut output = vec![0.0; size.0 * size.1 * size.2];
+        let mut frequency = 1.0;
+        for octave in &self.octaves {
+            let scale = (scale.0 * frequency, scale.1 * frequency, scale.2 * frequency);
+            octave.add_grid(&mut output, position, size, scale, frequency);
+            frequency /= 2.0;
+        }
+
+        output
+    }
+
+    /// The 2D noise over a grid of points, ordered by x, then z.
+    pub fn grid_2d(&self, (x, z): (f64, f64), (size_x, size_z): (usize, usize), (scale_x, scale_z): (f64, f64)) -> Vec<f64> {
+        self.grid((x, 10.0, z), (size_x, 1, size_z), (scale_x, 1.0, scale_z))
+    }
+}
+
+const GRADIENTS: [[f64; 2]; 12] = [
+    [1.0, 1.0], [-1.0, 1.0], [1.0, -1.0], [-1.0, -1.0],
+    [1.0, 0.0], [-1.0, 0.0], [1.0, 0.0], [-1.0, 0.0],
+    [0.0, 1.0], [0.0, -1.0], [0.0, 1.0], [0.0, -1.0],
+];
+
+/// 2D simplex noise, used for temperature and humidity.
+#[derive(Debug, Clone)]
+pub struct SimplexNoise {
+    permutations: [usize; 512],
+    x_offset: f64,
+    z_offset: f64,
+}
+
+impl SimplexNoise {
+    pub fn new(random: &mut JavaRandom) -> Self {
+        let x_offset = random.next_double() * 256.0;
+        let z_offset = random.next_double() * 256.0;
+        // The third offset is unused but still taken from the generator
+        let _ = random.next_double();
+
+        Self {
+            permutations: permutations(random),
+            x_offset,
+            z_offset,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_grid(
+        &self,
+        output: &mut [f64],
+        (x, z): (f64, f64),
+        (size_x, size_z): (usize, usize),
+        (scale_x, scale_z): (f64, f64),
+        amplitude: f64,
+    ) {
+        let skew = 0.5 * (3f64.sqrt() - 1.0);
+        let unskew = (3.0 - 3f64.sqrt()) / 6.0;
+        let p = &self.permutations;
+
+        let corner = |gradient: usize, x: f64, z: f64| {
+            let t = 0.5 - x * x - z * z;
+            if t < 0.0 {
+                0.0
+            } else {
+                let t = t * t;
+                t * t * (GRADIENTS[gradient][0] * x + GRADIENTS[gradient][1] * z)
+            }
+        };
+        // Java casts towards zero, and the original only corrects positive
+        // values
+        let wrap = |value: f64| if value > 0.0 { value as i32 } else { value as i32 - 1 };
+
+        let mut index = 0;
+        for ix in 0..size_x {
+            let x = (x + ix as f64) * scale_x + self.x_offset;
+            for iz in 0..size_z {
+                let z = (z + iz as f64) * scale_z + self.z_offset;
+
+                let s = (x + z) * skew;
+                let i = wrap(x + s);
+                let j = wrap(z + s);
+                let t = (i + j) as f64 * unskew;
+                let x0 = x - (i as f64 - t);
+                let z0 = z - (j as f64 - t);
+                let (i1, j1) = if x0 > z0 { (1, 0) } else { (0, 1) };
+                let x1 = x0 - i1 as f64 + unskew;
+                let z1 = z0 - j1 as f64 + unskew;
+                let x2 = x0 - 1.0 + 2.0 * unskew;
+                let z2 = z0 - 1.0 + 2.0 * unskew;
+
+                let ii = (i & 255) as usize;
+                let jj = (j & 255) as usize;
+                let g0 = p[ii + p[jj]] % 12;
+                let g1 = p[ii + i1 + p[jj + j1]] % 12;
+                let g2 = p[ii + 1 + p[jj + 1]] % 12;
+
+                let value = corner(g0, x0, z0) + corner(g1, x1, z1) + corner(g2, x2, z2);
+                output[index] += 70.0 * value * amplitude;
+                index += 1;
+            }
+        }
+    }
+}
+
+/// Several octaves of [`SimplexNoise`].
+#[derive(Debug, Clone)]
+pub struct SimplexOctaveNoise {
+    octaves: Vec<SimplexNoise>,
+}
+
+impl SimplexOctaveNoise {
+    pub fn new(random: &mut JavaRandom, octaves: usize) -> Self {
+        Self {
+            octaves: (0..octaves).map(|_| SimplexNoise::new(random)).collect(),
+        }
+    }
+
+    /// The noise over a grid of points, ordered by x, then z.
+    ///
+    /// Each octave is scaled by `frequency_step` and its amplitude divided by
+    /// 2, the same as the original's default.
+    pub fn grid(
+        &self,
+        position: (f64, f64),
+        size: (usize, usize),
+        scale: (f64, f64),
+        frequency_step: f64,
+    ) -> Vec<f64> {
+        let (scale_x, scale_z) = (scale.0 / 1.5, scale.1 / 1.5);
+        let mut output = vec![0.0; size.0 * size.1];
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        for octave in &self.octaves {
+            octave.add_grid(
+                &mut output,
+                position,
+                size,
+                (scale_x * frequency, scale_z * frequency),
+                0.55 / amplitude,
+            );
+            frequency *= frequency_step;
+            amplitude *= 0.5;
+        }
+
+        output
+    }
+}
@@ -0,0 +1,127 @@
+/// The linear congruential generator of `java.util.Random`, which the
+/// original world generator is built on.
+///
+/// Using the same generator means a seed produces the same terrain as it
+/// would for the original server.
+#[derive(Debug, Clone)]
+pub struct JavaRandom {
+    seed: i64,
+}
+
+impl JavaRandom {
+    const MULTIPLIER: i64 = 0x5DEECE66D;
+    const ADDEND: i64 = 0xB;
+    const MASK: i64 = (1 << 48) - 1;
+
+    pub fn new(seed: i64) -> Self {
+        let mut random = Self { seed: 0 };
+        random.set_seed(seed);
+        random
+    }
+
+    pub fn set_seed(&mut self, seed: i64) {
+        self.seed = (seed ^ Self::MULTIPLIER) & Self::MASK;
+    }
+
+    fn next(&mut self, bits: u32) -> i32 {
+        self.seed = self.seed
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(Self::ADDEND)
+            & Self::MASK;
+
+        (self.seed >> (48 - bits)) as i32
+    }
+
+    /// A random number from `0` up to but not including `bound`.
+    ///
+    /// Panics if `bound` is not positive, like the original throws.
+    pub fn next_int(&mut self, bound: i32) -> i32 {
+        assert!(bound > 0, "bound must be positive");
+
+        if bound & -bound == bound {
+            return ((bound as i64 * self.next(31) as i64) >> 31) as i32
+        }
+
+        loop {
+            let bits = self.next(31);
+            let value = bits % bound;
+            if bits.wrapping_sub(value).wrapping_add(bound - 1) >= 0 {
+                return value
+            }
+        }
+    }
+
+    pub fn next_long(&mut self) -> i64 {
+        ((self.next(32) as i64) << 32).wrapping_add(self.next(32) as i64)
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next(1) != 0
+    }
+
+    pub fn next_float(&mut self) -> f32 {
+        self.next(24) as f32 / (1 << 24) as f32
+    }
+
+    pub fn next_double(&mut self) -> f64 {
+        let high = (self.next(26) as i64) << 27;
+        let low = self.next(27) as i64;
+        (high + low) as f64 * (1.0 / (1i64 << 53) as f64)
+    }
+}
+
+/// The rounded value of pi used by the original generator, which is kept
+/// because it gives slightly different results than the exact value.
+#[allow(clippy::approx_constant)]
+pub const PI: f32 = 3.141593;
+
+/// Half of [`PI`], rounded the same way.
+#[allow(clippy::approx_constant)]
+pub const HALF_PI: f32 = 1.570796;
+
+/// The sine used by the original generator, which reads from a table of
+/// 65536 values rather than calculating it exactly.
+pub fn table_sin(value: f32) -> f32 {
+    let index = (value * 10430.38) as i32 & 0xFFFF;
+    (index as f64 * std::f64::consts::PI * 2.0 / 65536.0).sin() as f32
+}
+
+/// The cosine matching [`table_sin`].
+pub fn table_cos(value: f32) -> f32 {
+    let index = (value * 10430.38 + 16384.0) as i32 & 0xFFFF;
+    (index as f64 * std::f64::consts::PI * 2.0 / 65536.0).sin() as f32
+}
+
+/// Round down to an integer, like `MathHelper.floor_double`.
+pub fn floor(value: f64) -> i32 {
+    let truncated = value as i32;
+    if value < truncated as f64 { truncated - 1 } else { truncated }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checked against the output of `java.util.Random` for the same seeds.
+    #[test]
+    fn matches_java_random() {
+        let mut random = JavaRandom::new(8675309);
+        assert_eq!(random.next_int(16), 12);
+        assert_eq!(random.next_int(100), 88);
+        assert_eq!(random.next_int(1 << 20), 729624);
+        assert_eq!(random.next_int(1000000007), 173339072);
+        assert_eq!(random.next_long(), 3459198001235655623);
+        assert_eq!(random.next_long(), 2969410774914058961);
+        assert!(random.next_bool());
+        assert!(random.next_bool());
+        assert!(random.next_bool());
+        assert_eq!(random.next_float().to_bits(), 1060380870);
+        assert_eq!(random.next_float(), 0.088819206);
+        assert_eq!(random.next_double().to_bits(), 4598631647933957744);
+        assert_eq!(random.next_double(), 0.22756446094320648);
+
+        random.set_seed(-1);
+        assert_eq!(random.next_int(7), 3);
+        assert_eq!(random.next_long(), 8108487875070533655);
+    }
+}
@@ -0,0 +1,264 @@
+//! The inventory of a player, split into the sections the client sends with
+//! [`Packet5PlayerInventory`].
+
+use std::{error::Error, fmt::Display};
+
+use num_derive::FromPrimitive;
+
+use crate::{
+    blocks_items::{BlockItem, BlockItemID, ItemStack},
+    nbt::Tag,
+    packets::packet5_player_inventory::Packet5PlayerInventory,
+};
+
+/// The number of slots in the main inventory, including the hotbar.
+pub const MAIN_SIZE: usize = 36;
+
+/// The number of slots in the hotbar, which are the first slots of the main
+/// inventory.
+pub const HOTBAR_SIZE: usize = 9;
+
+/// The number of slots in the 2x2 crafting grid.
+pub const CRAFTING_SIZE: usize = 4;
+
+/// The number of armor slots, from the helmet down to the boots.
+pub const ARMOR_SIZE: usize = 4;
+
+/// A section of the inventory, by the type used for it in
+/// [`Packet5PlayerInventory`].
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(FromPrimitive)]
+pub enum InventorySection {
+    Main = -1,
+    Crafting = -2,
+    Armor = -3,
+}
+
+impl InventorySection {
+    pub const ALL: [Self; 3] = [Self::Main, Self::Crafting, Self::Armor];
+
+    /// The number of slots in the section.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Main => MAIN_SIZE,
+            Self::Crafting => CRAFTING_SIZE,
+            Self::Armor => ARMOR_SIZE,
+        }
+    }
+
+    /// The number of the first slot of the section in saved player data.
+    fn first_saved_slot(&self) -> usize {
+        match self {
+            Self::Main => 0,
+            Self::Crafting => 80,
+            Self::Armor => 100,
+        }
+    }
+}
+
+/// Why the contents of an inventory section sent by a client were refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InventoryError {
+    /// The section has the wrong number of slots
+    WrongSize {
+        section: InventorySection,
+        size: usize,
+    },
+
+    /// A slot contains an item which doesn't exist
+    UnknownItem(i16),
+
+    /// A slot contains more of an item than fits in it, or none at all
+    BadStackSize {
+        item: BlockItem,
+        size: i32,
+    },
+}
+
+impl Display for InventoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongSize { section, size } => write!(
+                f, "{section:?} inventory has {size} slots instead of {}", section.size()
+            ),
+            Self::UnknownItem(id) => write!(f, "unknown item {id}"),
+            Self::BadStackSize { item, size } => write!(
+                f, "stack of {size} {item:?}, but at most {} fit in a slot", item.max_stack_size()
+            ),
+        }
+    }
+}
+
+impl Error for InventoryError {}
+
+/// Every item a player carries.
+///
+/// The client is in charge of moving items around and crafting, and sends
+/// the whole of a section whenever it changes. The server only checks that
+/// what it is sent could be real, and keeps a copy to save and to check
+/// what the player is holding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inventory {
+    main: [Option<ItemStack>; MAIN_SIZE],
+    crafting: [Option<ItemStack>; CRAFTING_SIZE],
+    armor: [Option<ItemStack>; ARMOR_SIZE],
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            main: [None; MAIN_SIZE],
+            crafting: [None; CRAFTING_SIZE],
+            armor: [None; ARMOR_SIZE],
+        }
+    }
+}
+
+impl Inventory {
+    pub fn section(&self, section: InventorySection) -> &[Option<ItemStack>] {
+        match section {
+            InventorySection::Main => &self.main,
+            InventorySection::Crafting => &self.crafting,
+            InventorySection::Armor => &self.armor,
+        }
+    }
+
+    pub fn section_mut(&mut self, section: InventorySection) -> &mut [Option<ItemStack>] {
+        match section {
+            InventorySection::Main => &mut self.main,
+            InventorySection::Crafting => &mut self.crafting,
+            InventorySection::Armor => &mut self.armor,
+        }
+    }
+
+    /// Replace the contents of a section with what a client sent, if every
+    /// slot is valid.
+    pub fn set_section(&mut self, section: InventorySection, items: &[Option<ItemStack>]) -> Result<(), InventoryError> {
+        if items.len() != section.size() {
+            return Err(InventoryError::WrongSize { section, size: items.len() })
+        }
+
+        for item in items.iter().flatten() {
+            if item.item_id == BlockItem::Unknown {
+                return Err(InventoryError::UnknownItem(item.item_id.id()))
+            }
+            if item.stack_size < 1 || item.stack_size > item.item_id.max_stack_size() {
+                return Err(InventoryError::BadStackSize { item: item.item_id, size: item.stack_size })
+            }
+        }
+
+        self.section_mut(section).copy_from_slice(items);
+
+        Ok(())
+    }
+
+    /// The packet which sends the contents of a section to the client.
+    pub fn packet(&self, section: InventorySection) -> Packet5PlayerInventory {
+        Packet5PlayerInventory {
+            inventory_type: section as i32,
+            items: self.section(section).to_vec(),
+        }
+    }
+
+    /// Whether the item is in one of the slots of the hotbar, which are the
+    /// only items a player can hold.
+    pub fn hotbar_contains(&self, item: BlockItem) -> bool {
+        self.main[..HOTBAR_SIZE].iter()
+            .flatten()
+            .any(|s| s.item_id == item)
+    }
+
+    /// The `Inventory` list of saved player data, with a compound for every
+    /// slot which isn't empty.
+    pub fn to_nbt(&self) -> Tag {
+        let mut slots = Vec::new();
+        for section in InventorySection::ALL {
+            for (i, item) in self.section(section).iter().enumerate() {
+                let Some(item) = item else {
+                    continue
+                };
+
+                let mut slot = Tag::new_compound();
+                slot.insert("Slot", (section.first_saved_slot() + i) as i8);
+                slot.insert("id", item.item_id.id());
+                slot.insert("Count", item.stack_size as i8);
+                slot.insert("Damage", item.item_damage as i16);
+                slots.push(slot);
+            }
+        }
+
+        Tag::List(slots)
+    }
+
+    /// Read the `Inventory` list of saved player data, skipping any slots
+    /// which aren't valid.
+    pub fn from_nbt(tag: &Tag) -> Self {
+        let mut inventory = Self::default();
+        for slot in tag.as_list().unwrap_or_default() {
+            let read_slot = || {
+                let number = slot.get("Slot")?.as_byte()? as u8 as usize;
+                let item = ItemStack::new(
+                    slot.get("id")?.as_short()? as i32,
+                    slot.get("Count")?.as_byte()? as i32,
+                    slot.get("Damage").and_then(Tag::as_short).unwrap_or(0) as i32,
+                );
+                Some((number, item))
+            };
+            let Some((number, item)) = read_slot() else {
+                continue
+            };
+            if item.item_id == BlockItem::Unknown || item.stack_size < 1 {
+                continue
+            }
+
+            let destination = InventorySection::ALL.into_iter().find_map(|section| {
+                let index = number.checked_sub(section.first_saved_slot())?;
+                (index < section.size()).then_some((section, index))
+            });
+            if let Some((section, index)) = destination {
+                inventory.section_mut(section)[index] = Some(item);
+            }
+        }
+
+        inventory
+    }
+
+    /// Add an item to the main inventory the way the client does when it
+    /// receives an `AddToInventory` packet, filling up stacks of the same
+    /// item before using empty slots.
+    ///
+    /// Returns how many of the items fit.
+    pub fn add(&mut self, item: ItemStack) -> i32 {
+        let max_stack_size = item.item_id.max_stack_size();
+        let mut left = item.stack_size;
+
+        // Items which don't stack always take an empty slot
+        if max_stack_size > 1 {
+            for stack in self.main.iter_mut().flatten() {
+                if left == 0 {
+                    break
+                }
+                if stack.item_id != item.item_id || stack.item_damage != item.item_damage {
+                    continue
+                }
+
+                let added = left.min(max_stack_size - stack.stack_size).max(0);
+                stack.stack_size += added;
+                left -= added;
+            }
+        }
+
+        for slot in self.main.iter_mut().filter(|s| s.is_none()) {
+            if left == 0 {
+                break
+            }
+
+            let added = left.min(max_stack_size);
+            *slot = Some(ItemStack { stack_size: added, ..item });
+            left -= added;
+        }
+
+        item.stack_size - left
+    }
+}
@@ -0,0 +1,19 @@
+pub mod mcstring;
+pub mod chunk;
+pub mod position;
+pub mod state;
+pub mod player;
+pub mod blocks_items;
+pub mod inventory;
+pub mod entity_id;
+pub mod packets;
+pub mod error;
+pub mod connection;
+pub mod server;
+pub mod nbt;
+pub mod world;
+pub mod generator;
+pub mod config;
+pub mod commands;
+pub mod access;
+pub mod auth;
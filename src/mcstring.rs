@@ -12,6 +12,10 @@ impl MCString {
     pub fn len(&self) -> usize {
         self.len as usize
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
 }
 
 impl TryFrom<&str> for MCString {
@@ -46,10 +50,25 @@ impl TryFrom<String> for MCString {
 
 impl Display for MCString {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", String::from_utf8(self.chars.clone()).unwrap())
+        write!(f, "{}", String::from_utf8_lossy(&self.chars))
     }
 }
 
+/// Remove the `§` color codes from a chat message, for logging.
+pub fn strip_color_codes(message: &str) -> String {
+    let mut stripped = String::with_capacity(message.len());
+    let mut chars = message.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
 pub trait ReadMCString: io::Read {
     #[inline]
     fn read_mcstring(&mut self) -> Result<MCString, io::Error> {
@@ -0,0 +1,416 @@
+use std::{collections::BTreeMap, io::{self, Read, Write}};
+
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+
+use crate::mcstring::{MCString, ReadMCString, WriteMCString};
+
+/// The type ID of a [`Tag`].
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(FromPrimitive)]
+pub enum TagType {
+    End = 0,
+    Byte = 1,
+    Short = 2,
+    Int = 3,
+    Long = 4,
+    Float = 5,
+    Double = 6,
+    ByteArray = 7,
+    String = 8,
+    List = 9,
+    Compound = 10,
+}
+
+/// A Named Binary Tag.
+///
+/// Lists are stored as a [`Vec`] of tags which must all have the same type,
+/// and compounds as a map from names to tags.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<u8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(BTreeMap<String, Tag>),
+}
+
+impl Tag {
+    pub fn tag_type(&self) -> TagType {
+        match self {
+            Tag::Byte(_) => TagType::Byte,
+            Tag::Short(_) => TagType::Short,
+            Tag::Int(_) => TagType::Int,
+            Tag::Long(_) => TagType::Long,
+            Tag::Float(_) => TagType::Float,
+            Tag::Double(_) => TagType::Double,
+            Tag::ByteArray(_) => TagType::ByteArray,
+            Tag::String(_) => TagType::String,
+            Tag::List(_) => TagType::List,
+            Tag::Compound(_) => TagType::Compound,
+        }
+    }
+
+    /// Create an empty compound tag.
+    pub fn new_compound() -> Self {
+        Tag::Compound(BTreeMap::new())
+    }
+
+    /// Get a tag inside a compound by name.
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.as_compound()?.get(name)
+    }
+
+    /// Insert a tag into a compound, doing nothing if this isn't a compound.
+    pub fn insert<T: Into<Tag>>(&mut self, name: &str, tag: T) {
+        if let Tag::Compound(compound) = self {
+            compound.insert(name.to_string(), tag.into());
+        }
+    }
+
+    pub fn as_byte(&self) -> Option<i8> {
+        match self {
+            Tag::Byte(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_short(&self) -> Option<i16> {
+        match self {
+            Tag::Short(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Tag::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_long(&self) -> Option<i64> {
+        match self {
+            Tag::Long(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            Tag::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_double(&self) -> Option<f64> {
+        match self {
+            Tag::Double(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_byte_array(&self) -> Option<&[u8]> {
+        match self {
+            Tag::ByteArray(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&BTreeMap<String, Tag>> {
+        match self {
+            Tag::Compound(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    fn read_payload<R: Read>(tag_type: TagType, input: &mut R) -> Result<Self, io::Error> {
+        Ok(match tag_type {
+            TagType::End => return Err(invalid_data("unexpected end tag")),
+            TagType::Byte => Tag::Byte(input.read_i8()?),
+            TagType::Short => Tag::Short(input.read_i16::<BE>()?),
+            TagType::Int => Tag::Int(input.read_i32::<BE>()?),
+            TagType::Long => Tag::Long(input.read_i64::<BE>()?),
+            TagType::Float => Tag::Float(input.read_f32::<BE>()?),
+            TagType::Double => Tag::Double(input.read_f64::<BE>()?),
+            TagType::ByteArray => {
+                let length = read_length(input)?;
+
+                // Read as much as there is rather than trusting the length
+                // with an allocation
+                let mut bytes = Vec::new();
+                input.by_ref().take(length as u64).read_to_end(&mut bytes)?;
+                if bytes.len() != length {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "byte array is truncated"))
+                }
+                Tag::ByteArray(bytes)
+            }
+            TagType::String => Tag::String(input.read_mcstring()?.to_string()),
+            TagType::List => {
+                let element_type = read_tag_type(input)?;
+                let length = read_length(input)?;
+                let mut list = Vec::new();
+                for _ in 0..length {
+                    list.push(Tag::read_payload(element_type, input)?);
+                }
+                Tag::List(list)
+            }
+            TagType::Compound => {
+                let mut compound = BTreeMap::new();
+                loop {
+                    let tag_type = read_tag_type(input)?;
+                    if tag_type == TagType::End {
+                        break;
+                    }
+                    let name = input.read_mcstring()?.to_string();
+                    compound.insert(name, Tag::read_payload(tag_type, input)?);
+                }
+                Tag::Compound(compound)
+            }
+        })
+    }
+
+    fn write_payload<W: Write>(&self, output: &mut W) -> Result<(), io::Error> {
+        match self {
+            Tag::Byte(v) => output.write_i8(*v)?,
+            Tag::Short(v) => output.write_i16::<BE>(*v)?,
+            Tag::Int(v) => output.write_i32::<BE>(*v)?,
+            Tag::Long(v) => output.write_i64::<BE>(*v)?,
+            Tag::Float(v) => output.write_f32::<BE>(*v)?,
+            Tag::Double(v) => output.write_f64::<BE>(*v)?,
+            Tag::ByteArray(v) => {
+                output.write_i32::<BE>(v.len() as i32)?;
+                output.write_all(v)?;
+            }
+            Tag::String(v) => output.write_mcstring(&to_mcstring(v)?)?,
+            Tag::List(v) => {
+                // Empty lists are written as lists of bytes, like the original
+                let element_type = v.first().map_or(TagType::Byte, |t| t.tag_type());
+                output.write_u8(element_type as u8)?;
+                output.write_i32::<BE>(v.len() as i32)?;
+                for tag in v {
+                    if tag.tag_type() != element_type {
+                        return Err(invalid_data("list elements must all have the same type"))
+                    }
+                    tag.write_payload(output)?;
+                }
+            }
+            Tag::Compound(v) => {
+                for (name, tag) in v {
+                    write_named(output, name, tag)?;
+                }
+                output.write_u8(TagType::End as u8)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! impl_from_for_tag {
+    ($($t:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$t> for Tag {
+                fn from(value: $t) -> Self {
+                    Tag::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_for_tag! {
+    i8 => Byte,
+    i16 => Short,
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    Vec<u8> => ByteArray,
+    String => String,
+    &str => String,
+    Vec<Tag> => List,
+    BTreeMap<String, Tag> => Compound,
+}
+
+/// A type which is stored as a compound tag, such as `level.dat` or the data
+/// of a player.
+pub trait NbtCompound: Sized {
+    /// Convert the value to a compound tag
+    fn to_nbt(&self) -> Tag;
+
+    /// Read the value from a compound tag, returning [`None`] if a required
+    /// tag is missing or has the wrong type
+    fn from_nbt(tag: &Tag) -> Option<Self>;
+}
+
+/// Read an uncompressed NBT stream, returning the name and the root tag.
+pub fn read<R: Read>(input: &mut R) -> Result<(String, Tag), io::Error> {
+    read_named(input)
+}
+
+/// Write an uncompressed NBT stream with the given root tag.
+pub fn write<W: Write>(output: &mut W, name: &str, tag: &Tag) -> Result<(), io::Error> {
+    write_named(output, name, tag)
+}
+
+/// Read a gzip compressed NBT file, returning the name and the root tag.
+pub fn read_gzip<R: Read>(input: R) -> Result<(String, Tag), io::Error> {
+    read_named(&mut GzDecoder::new(input))
+}
+
+/// Write a gzip compressed NBT file with the given root tag.
+pub fn write_gzip<W: Write>(output: W, name: &str, tag: &Tag) -> Result<(), io::Error> {
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    write_named(&mut encoder, name, tag)?;
+    encoder.finish()?;
+
+    Ok(())
+}
+
+/// Read the length of a byte array or list, which can't be negative.
+fn read_length<R: Read>(input: &mut R) -> Result<usize, io::Error> {
+    let length = input.read_i32::<BE>()?;
+    usize::try_from(length).map_err(|_| invalid_data("negative length"))
+}
+
+fn read_named<R: Read>(input: &mut R) -> Result<(String, Tag), io::Error> {
+    let tag_type = read_tag_type(input)?;
+    let name = input.read_mcstring()?.to_string();
+    let tag = Tag::read_payload(tag_type, input)?;
+
+    Ok((name, tag))
+}
+
+fn write_named<W: Write>(output: &mut W, name: &str, tag: &Tag) -> Result<(), io::Error> {
+    output.write_u8(tag.tag_type() as u8)?;
+    output.write_mcstring(&to_mcstring(name)?)?;
+    tag.write_payload(output)
+}
+
+fn read_tag_type<R: Read>(input: &mut R) -> Result<TagType, io::Error> {
+    let id = input.read_u8()?;
+    TagType::from_u8(id).ok_or_else(|| invalid_data(&format!("unknown tag type {id}")))
+}
+
+fn to_mcstring(string: &str) -> Result<MCString, io::Error> {
+    MCString::try_from(string).map_err(|_| invalid_data("string is too long"))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A compound with every type of tag, including nested lists and
+    /// compounds.
+    fn every_tag() -> Tag {
+        let mut inner = Tag::new_compound();
+        inner.insert("Name", "inner");
+        inner.insert("Empty", Tag::new_compound());
+
+        let mut root = Tag::new_compound();
+        root.insert("Byte", -5i8);
+        root.insert("Short", -300i16);
+        root.insert("Int", 70000i32);
+        root.insert("Long", i64::MIN);
+        root.insert("Float", 1.5f32);
+        root.insert("Double", -0.25f64);
+        root.insert("ByteArray", vec![0u8, 1, 255]);
+        root.insert("String", "Hello, world! é");
+        root.insert("List", vec![Tag::Int(1), Tag::Int(2)]);
+        root.insert("EmptyList", Vec::<Tag>::new());
+        root.insert("Lists", vec![
+            Tag::List(vec![Tag::Short(1)]),
+            Tag::List(vec![Tag::String("a".to_string()), Tag::String("b".to_string())]),
+        ]);
+        root.insert("Compounds", vec![inner.clone(), Tag::new_compound()]);
+        root.insert("Compound", inner);
+
+        root
+    }
+
+    fn encode(tag: &Tag) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write(&mut bytes, "root", tag).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn round_trip() {
+        let tag = every_tag();
+        let bytes = encode(&tag);
+
+        assert_eq!(read(&mut bytes.as_slice()).unwrap(), ("root".to_string(), tag));
+    }
+
+    #[test]
+    fn round_trip_gzip() {
+        let tag = every_tag();
+        let mut bytes = Vec::new();
+        write_gzip(&mut bytes, "root", &tag).unwrap();
+
+        assert_eq!(&bytes[..2], [0x1f, 0x8b]);
+        assert_eq!(read_gzip(bytes.as_slice()).unwrap(), ("root".to_string(), tag));
+    }
+
+    #[test]
+    fn rejects_mixed_lists() {
+        let mut root = Tag::new_compound();
+        root.insert("List", vec![Tag::Int(1), Tag::Byte(2)]);
+
+        assert!(write(&mut Vec::new(), "", &root).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let bytes = encode(&every_tag());
+        for length in 0..bytes.len() {
+            assert!(read(&mut &bytes[..length]).is_err(), "read {length} of {} bytes", bytes.len());
+        }
+    }
+
+    #[test]
+    fn rejects_negative_lengths() {
+        // An unnamed byte array, then an unnamed list of ints
+        let byte_array = [&[TagType::ByteArray as u8, 0, 0][..], &(-1i32).to_be_bytes()].concat();
+        let list = [&[TagType::List as u8, 0, 0, TagType::Int as u8][..], &(-1i32).to_be_bytes()].concat();
+
+        for bytes in [byte_array, list] {
+            let error = read(&mut bytes.as_slice()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn rejects_lengths_longer_than_the_input() {
+        let bytes = [&[TagType::ByteArray as u8, 0, 0][..], &i32::MAX.to_be_bytes(), &[1, 2, 3]].concat();
+
+        assert!(read(&mut bytes.as_slice()).is_err());
+    }
+}
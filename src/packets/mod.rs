@@ -1,8 +1,106 @@
 use std::io::{self, Read, Write};
 
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+
+use crate::{chunk::{MapChunk, PreChunk}, error::ProtocolError};
+
+use self::{
+    packet0_keep_alive::Packet0KeepAlive,
+    packet1_login::Packet1Login,
+    packet2_handshake::Packet2Handshake,
+    packet3_chat::Packet3Chat,
+    packet4_update_time::Packet4UpdateTime,
+    packet5_player_inventory::Packet5PlayerInventory,
+    packet6_spawn_position::Packet6SpawnPosition,
+    packet7_use_entity::Packet7UseEntity,
+    packet8_update_health::Packet8UpdateHealth,
+    packet9_respawn::Packet9Respawn,
+    packet10_flying::Packet10Flying,
+    packet11_player_position::Packet11PlayerPosition,
+    packet12_player_look::Packet12PlayerLook,
+    packet13_player_look_move::Packet13PlayerLookMove,
+    packet14_block_dig::Packet14BlockDig,
+    packet15_place::Packet15Place,
+    packet16_block_item_switch::Packet16BlockItemSwitch,
+    packet17_add_to_inventory::Packet17AddToInventory,
+    packet18_arm_animation::Packet18ArmAnimation,
+    packet20_named_entity_spawn::Packet20NamedEntitySpawn,
+    packet21_pickup_spawn::Packet21PickupSpawn,
+    packet22_collect::Packet22Collect,
+    packet23_vehicle_spawn::Packet23VehicleSpawn,
+    packet24_mob_spawn::Packet24MobSpawn,
+    packet28_entity_velocity::Packet28EntityVelocity,
+    packet29_destroy_entity::Packet29DestroyEntity,
+    packet30_entity::Packet30Entity,
+    packet31_rel_entity_move::Packet31RelEntityMove,
+    packet32_entity_look::Packet32EntityLook,
+    packet33_rel_entity_move_look::Packet33RelEntityMoveLook,
+    packet34_entity_teleport::Packet34EntityTeleport,
+    packet38_entity_status::Packet38EntityStatus,
+    packet39_attach_entity::Packet39AttachEntity,
+    packet52_multi_block_change::Packet52MultiBlockChange,
+    packet53_block_change::Packet53BlockChange,
+    packet59_complex_entity::Packet59ComplexEntity,
+    packet60_explosion::Packet60Explosion,
+    packet100_open_window::Packet100OpenWindow,
+    packet101_close_window::Packet101CloseWindow,
+    packet102_window_click::Packet102WindowClick,
+    packet103_set_slot::Packet103SetSlot,
+    packet104_window_items::Packet104WindowItems,
+    packet105_update_progressbar::Packet105UpdateProgressbar,
+    packet106_transaction::Packet106Transaction,
+    packet130_update_sign::Packet130UpdateSign,
+    packet255_kick_disconnect::Packet255KickDisconnect,
+};
+
+pub mod packet0_keep_alive;
 pub mod packet1_login;
+pub mod packet2_handshake;
+pub mod packet3_chat;
+pub mod packet4_update_time;
+pub mod packet5_player_inventory;
+pub mod packet6_spawn_position;
+pub mod packet7_use_entity;
+pub mod packet8_update_health;
+pub mod packet9_respawn;
+pub mod packet10_flying;
+pub mod packet11_player_position;
+pub mod packet12_player_look;
+pub mod packet13_player_look_move;
+pub mod packet14_block_dig;
 pub mod packet15_place;
-pub mod packet101;
+pub mod packet16_block_item_switch;
+pub mod packet17_add_to_inventory;
+pub mod packet18_arm_animation;
+pub mod packet20_named_entity_spawn;
+pub mod packet21_pickup_spawn;
+pub mod packet22_collect;
+pub mod packet23_vehicle_spawn;
+pub mod packet24_mob_spawn;
+pub mod packet28_entity_velocity;
+pub mod packet29_destroy_entity;
+pub mod packet30_entity;
+pub mod packet31_rel_entity_move;
+pub mod packet32_entity_look;
+pub mod packet33_rel_entity_move_look;
+pub mod packet34_entity_teleport;
+pub mod packet38_entity_status;
+pub mod packet39_attach_entity;
+pub mod packet52_multi_block_change;
+pub mod packet53_block_change;
+pub mod packet59_complex_entity;
+pub mod packet60_explosion;
+pub mod packet100_open_window;
+pub mod packet101_close_window;
+pub mod packet102_window_click;
+pub mod packet103_set_slot;
+pub mod packet104_window_items;
+pub mod packet105_update_progressbar;
+pub mod packet106_transaction;
+pub mod packet130_update_sign;
+pub mod packet255_kick_disconnect;
 
 /// A packet for communicating across the network.
 pub trait Packet
@@ -17,3 +115,445 @@ pub trait Packet
     /// The size of the packet in bytes
     fn size(&self) -> usize;
 }
+
+/// The ID of every packet in the protocol.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(FromPrimitive)]
+pub enum Command {
+    KeepAlive = 0x00,
+    Login = 0x01,
+    Handshake = 0x02,
+    ChatMessage = 0x03,
+    TimeUpdate = 0x04,
+    PlayerInventory = 0x05,
+    SpawnPosition = 0x06,
+    UseEntity = 0x07,
+    UpdateHealth = 0x08,
+    Respawn = 0x09,
+    Player = 0x0A,
+    PlayerPosition = 0x0B,
+    PlayerLook = 0x0C,
+    PlayerPositionAndLook = 0x0D,
+    PlayerDigging = 0x0E,
+    PlayerBlockPlacement = 0x0F,
+    HoldingChange = 0x10,
+    AddToInventory = 0x11,
+    Animation = 0x12,
+    NamedEntitySpawn = 0x14,
+    PickupSpawn = 0x15,
+    CollectItem = 0x16,
+    AddObject = 0x17,
+    MobSpawn = 0x18,
+    EntityVelocity = 0x1C,
+    DestroyEntity = 0x1D,
+    Entity = 0x1E,
+    EntityRelativeMove = 0x1F,
+    EntityLook = 0x20,
+    EntityLookAndRelativeMove = 0x21,
+    EntityTeleport = 0x22,
+    EntityStatus = 0x26,
+    AttachEntity = 0x27,
+    PreChunk = 0x32,
+    MapChunk = 0x33,
+    MultiBlockChange = 0x34,
+    BlockChange = 0x35,
+    ComplexEntities = 0x3B,
+    Explosion = 0x3C,
+    OpenWindow = 0x64,
+    CloseWindow = 0x65,
+    WindowClick = 0x66,
+    SetSlot = 0x67,
+    WindowItems = 0x68,
+    UpdateProgressBar = 0x69,
+    Transaction = 0x6A,
+    UpdateSign = 0x82,
+    Disconnect = 0xFF,
+}
+
+/// Wraps a reader and keeps a copy of every byte read through it, so the
+/// bytes of a packet which failed to decode can be logged.
+pub struct RecordingReader<R: Read> {
+    inner: R,
+    recorded: Vec<u8>,
+}
+
+impl<R: Read> RecordingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            recorded: Vec::new(),
+        }
+    }
+
+    /// The bytes read since the last call to [`RecordingReader::clear`]
+    pub fn recorded(&self) -> &[u8] {
+        &self.recorded
+    }
+
+    /// Forget the recorded bytes, usually at the start of a new packet
+    pub fn clear(&mut self) {
+        self.recorded.clear()
+    }
+}
+
+impl<R: Read> Read for RecordingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.recorded.extend_from_slice(&buf[..count]);
+
+        Ok(count)
+    }
+}
+
+/// Generates an enum over every packet that can be sent in one direction,
+/// which reads and writes the packet ID in front of the packet itself.
+macro_rules! packet_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident($packet:ty)),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum $name {
+            $($variant($packet),)*
+        }
+
+        impl $name {
+            /// The ID of this packet
+            pub fn id(&self) -> Command {
+                match self {
+                    $(Self::$variant(_) => Command::$variant,)*
+                }
+            }
+
+            /// Read a packet ID followed by the packet itself
+            pub fn decode<R: Read>(input: &mut R) -> Result<Self, ProtocolError> {
+                let id = input.read_u8()?;
+                let command = Command::from_u8(id).ok_or(ProtocolError::UnknownPacket(id))?;
+
+                Self::read_body(command, input)
+            }
+
+            /// Read the body of a packet whose ID has already been read
+            pub fn read_body<R: Read>(id: Command, input: &mut R) -> Result<Self, ProtocolError> {
+                let body = match id {
+                    $(Command::$variant => <$packet>::read_from(input).map(Self::$variant),)*
+                    #[allow(unreachable_patterns)]
+                    c => return Err(ProtocolError::WrongDirection(c)),
+                };
+
+                body.map_err(|e| match e.kind() {
+                    io::ErrorKind::InvalidData => ProtocolError::Malformed(id, e.to_string()),
+                    _ => ProtocolError::Io(e),
+                })
+            }
+        }
+
+        impl Packet for $name {
+            fn read_from<R: Read>(input: &mut R) -> Result<Self, io::Error> {
+                Ok(Self::decode(input)?)
+            }
+
+            fn write_into<W: Write>(&self, output: &mut W) -> Result<(), io::Error> {
+                output.write_u8(self.id() as u8)?;
+                match self {
+                    $(Self::$variant(p) => p.write_into(output),)*
+                }
+            }
+
+            fn size(&self) -> usize {
+                1 + match self {
+                    $(Self::$variant(p) => p.size(),)*
+                }
+            }
+        }
+
+        $(
+            impl From<$packet> for $name {
+                fn from(packet: $packet) -> Self {
+                    Self::$variant(packet)
+                }
+            }
+        )*
+    };
+}
+
+packet_enum! {
+    /// A packet sent from the server to the client.
+    ClientboundPacket {
+        KeepAlive(Packet0KeepAlive),
+        Login(Packet1Login),
+        Handshake(Packet2Handshake),
+        ChatMessage(Packet3Chat),
+        TimeUpdate(Packet4UpdateTime),
+        PlayerInventory(Packet5PlayerInventory),
+        SpawnPosition(Packet6SpawnPosition),
+        UpdateHealth(Packet8UpdateHealth),
+        Respawn(Packet9Respawn),
+        PlayerPositionAndLook(Packet13PlayerLookMove),
+        HoldingChange(Packet16BlockItemSwitch),
+        AddToInventory(Packet17AddToInventory),
+        Animation(Packet18ArmAnimation),
+        NamedEntitySpawn(Packet20NamedEntitySpawn),
+        PickupSpawn(Packet21PickupSpawn),
+        CollectItem(Packet22Collect),
+        AddObject(Packet23VehicleSpawn),
+        MobSpawn(Packet24MobSpawn),
+        EntityVelocity(Packet28EntityVelocity),
+        DestroyEntity(Packet29DestroyEntity),
+        Entity(Packet30Entity),
+        EntityRelativeMove(Packet31RelEntityMove),
+        EntityLook(Packet32EntityLook),
+        EntityLookAndRelativeMove(Packet33RelEntityMoveLook),
+        EntityTeleport(Packet34EntityTeleport),
+        EntityStatus(Packet38EntityStatus),
+        AttachEntity(Packet39AttachEntity),
+        PreChunk(PreChunk),
+        MapChunk(MapChunk),
+        MultiBlockChange(Packet52MultiBlockChange),
+        BlockChange(Packet53BlockChange),
+        ComplexEntities(Packet59ComplexEntity),
+        Explosion(Packet60Explosion),
+        OpenWindow(Packet100OpenWindow),
+        CloseWindow(Packet101CloseWindow),
+        SetSlot(Packet103SetSlot),
+        WindowItems(Packet104WindowItems),
+        UpdateProgressBar(Packet105UpdateProgressbar),
+        Transaction(Packet106Transaction),
+        UpdateSign(Packet130UpdateSign),
+        Disconnect(Packet255KickDisconnect),
+    }
+}
+
+packet_enum! {
+    /// A packet sent from the client to the server.
+    ServerboundPacket {
+        KeepAlive(Packet0KeepAlive),
+        Login(Packet1Login),
+        Handshake(Packet2Handshake),
+        ChatMessage(Packet3Chat),
+        PlayerInventory(Packet5PlayerInventory),
+        UseEntity(Packet7UseEntity),
+        Respawn(Packet9Respawn),
+        Player(Packet10Flying),
+        PlayerPosition(Packet11PlayerPosition),
+        PlayerLook(Packet12PlayerLook),
+        PlayerPositionAndLook(Packet13PlayerLookMove),
+        PlayerDigging(Packet14BlockDig),
+        PlayerBlockPlacement(Packet15Place),
+        HoldingChange(Packet16BlockItemSwitch),
+        Animation(Packet18ArmAnimation),
+        ComplexEntities(Packet59ComplexEntity),
+        CloseWindow(Packet101CloseWindow),
+        WindowClick(Packet102WindowClick),
+        Transaction(Packet106Transaction),
+        UpdateSign(Packet130UpdateSign),
+        Disconnect(Packet255KickDisconnect),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use crate::{
+        blocks_items::{BlockItem, BlockItemID, ItemStack},
+        chunk::BlockArray,
+        mcstring::MCString,
+        player::{DiggingStatus, Direction},
+        position::{PlayerLook, PlayerPosition, PlayerPositionLook},
+    };
+
+    use super::*;
+
+    fn string(s: &str) -> MCString {
+        MCString::try_from(s).unwrap()
+    }
+
+    fn position_look() -> PlayerPositionLook {
+        PlayerPositionLook {
+            position: PlayerPosition {
+                position_x: 12.5,
+                position_y: 64.0,
+                stance: 65.62,
+                position_z: -3.25,
+            },
+            look: PlayerLook {
+                yaw: 90.0,
+                pitch: -15.5,
+            },
+        }
+    }
+
+    fn items() -> Vec<Option<ItemStack>> {
+        vec![Some(ItemStack::new(1, 64, 0)), None, Some(ItemStack::new(276, 1, 12))]
+    }
+
+    /// Check that a packet is written in as many bytes as it says, and reads
+    /// back the same.
+    fn assert_round_trip<P: Packet + PartialEq + Debug>(packet: &P) {
+        let mut bytes = Vec::new();
+        packet.write_into(&mut bytes).unwrap();
+        assert_eq!(packet.size(), bytes.len(), "size of {packet:?}");
+
+        let mut input = bytes.as_slice();
+        assert_eq!(&P::read_from(&mut input).unwrap(), packet);
+        assert!(input.is_empty(), "{} bytes left after {packet:?}", input.len());
+    }
+
+    #[test]
+    fn clientbound_round_trip() {
+        let packets: Vec<ClientboundPacket> = vec![
+            Packet0KeepAlive.into(),
+            Packet1Login::new(5, -123456789, -1).into(),
+            Packet2Handshake { username: string("-") }.into(),
+            Packet3Chat { message: string("§eHello, world!") }.into(),
+            Packet4UpdateTime { time: 24000 * 3 + 13000 }.into(),
+            Packet5PlayerInventory { inventory_type: -1, items: items() }.into(),
+            Packet6SpawnPosition { x: -10, y: 64, z: 1000 }.into(),
+            Packet8UpdateHealth { health: 20 }.into(),
+            Packet9Respawn.into(),
+            Packet13PlayerLookMove::new_clientbound(position_look()).into(),
+            Packet16BlockItemSwitch { entity_id: 3, item: BlockItem::from_id(4) }.into(),
+            Packet17AddToInventory { item: ItemStack::new(263, 16, 0) }.into(),
+            Packet18ArmAnimation { entity_id: 3, animation: 1 }.into(),
+            Packet20NamedEntitySpawn {
+                entity_id: 3,
+                name: string("Notch"),
+                x: 400,
+                y: 2048,
+                z: -96,
+                rotation: 64,
+                pitch: -10,
+                current_item: 0,
+            }.into(),
+            Packet21PickupSpawn {
+                entity_id: 4,
+                item_id: 3,
+                count: 1,
+                x: 1,
+                y: 2,
+                z: 3,
+                rotation: 4,
+                pitch: 5,
+                roll: 6,
+            }.into(),
+            Packet22Collect { collected_entity_id: 4, collector_entity_id: 3 }.into(),
+            Packet23VehicleSpawn { entity_id: 5, object_type: 10, x: 1, y: 2, z: 3 }.into(),
+            Packet24MobSpawn { entity_id: 6, mob_type: 90, x: 1, y: 2, z: 3, yaw: 4, pitch: 5 }.into(),
+            Packet28EntityVelocity { entity_id: 6, motion_x: -100, motion_y: 0, motion_z: 8000 }.into(),
+            Packet29DestroyEntity { entity_id: 6 }.into(),
+            Packet30Entity { entity_id: 6 }.into(),
+            Packet31RelEntityMove { entity_id: 6, x: -1, y: 0, z: 127 }.into(),
+            Packet32EntityLook { entity_id: 6, yaw: -128, pitch: 0 }.into(),
+            Packet33RelEntityMoveLook { entity_id: 6, x: 1, y: 2, z: 3, yaw: 4, pitch: 5 }.into(),
+            Packet34EntityTeleport { entity_id: 6, x: 1, y: 2, z: 3, yaw: 4, pitch: 5 }.into(),
+            Packet38EntityStatus { entity_id: 6, status: 2 }.into(),
+            Packet39AttachEntity { entity_id: 3, vehicle_id: -1 }.into(),
+            PreChunk::new_load(-2, 7).into(),
+            MapChunk::new(-2, 7, &BlockArray::new_air()).into(),
+            Packet52MultiBlockChange {
+                chunk_x: 1,
+                chunk_z: -1,
+                coordinates: vec![0x1234, 0x0567],
+                types: vec![1, 0],
+                metadata: vec![0, 3],
+            }.into(),
+            Packet53BlockChange { x: -5, y: 127, z: 5, block_type: 20, metadata: 0 }.into(),
+            Packet59ComplexEntity { x: 1, y: 64, z: 2, data: vec![1, 2, 3, 4] }.into(),
+            Packet60Explosion {
+                x: 0.5,
+                y: 64.0,
+                z: -0.5,
+                size: 4.0,
+                destroyed_blocks: vec![(0, 0, 0), (-1, 2, -3)],
+            }.into(),
+            Packet100OpenWindow { window_id: 1, inventory_type: 0, title: string("Chest"), slots: 27 }.into(),
+            Packet101CloseWindow { window_id: 1 }.into(),
+            Packet103SetSlot { window_id: 0, slot: 36, item: Some(ItemStack::new(3, 64, 0)) }.into(),
+            Packet104WindowItems { window_id: 0, items: items() }.into(),
+            Packet105UpdateProgressbar { window_id: 2, progress_bar: 0, value: 150 }.into(),
+            Packet106Transaction { window_id: 0, action_number: 12, accepted: true }.into(),
+            Packet130UpdateSign {
+                x: 10,
+                y: 70,
+                z: -10,
+                lines: [string("Hello"), string(""), string("a sign"), string("")],
+            }.into(),
+            Packet255KickDisconnect { reason: string("The server is full!") }.into(),
+        ];
+
+        for packet in &packets {
+            assert_round_trip(packet);
+        }
+    }
+
+    #[test]
+    fn serverbound_round_trip() {
+        let mut login = Packet1Login::new(8, 0, 0);
+        login.username = string("Player");
+        login.password = string("Password");
+
+        let packets: Vec<ServerboundPacket> = vec![
+            Packet0KeepAlive.into(),
+            login.into(),
+            Packet2Handshake { username: string("Player") }.into(),
+            Packet3Chat { message: string("/time set day") }.into(),
+            Packet5PlayerInventory { inventory_type: -3, items: vec![None; 4] }.into(),
+            Packet7UseEntity { player_id: 3, target_id: 4, left_click: true }.into(),
+            Packet9Respawn.into(),
+            Packet10Flying { on_ground: true }.into(),
+            Packet11PlayerPosition { position: position_look().position, on_ground: false }.into(),
+            Packet12PlayerLook { look: position_look().look, on_ground: true }.into(),
+            Packet13PlayerLookMove { position_look: position_look(), on_ground: true }.into(),
+            Packet14BlockDig {
+                status: DiggingStatus::BlockBroken,
+                x: -1,
+                y: 63,
+                z: 1,
+                face: Direction::PosY,
+            }.into(),
+            Packet15Place {
+                id: BlockItem::from_id(4),
+                x_position: -1,
+                y_position: 63,
+                z_position: 1,
+                direction: 1,
+                amount: Some(10),
+                health: Some(0),
+            }.into(),
+            // Using an empty hand has no amount or damage
+            Packet15Place {
+                id: BlockItem::Unknown,
+                x_position: -1,
+                y_position: 255,
+                z_position: -1,
+                direction: 255,
+                amount: None,
+                health: None,
+            }.into(),
+            Packet16BlockItemSwitch { entity_id: 0, item: BlockItem::from_id(276) }.into(),
+            Packet18ArmAnimation { entity_id: 3, animation: 1 }.into(),
+            Packet59ComplexEntity { x: 1, y: 64, z: 2, data: Vec::new() }.into(),
+            Packet101CloseWindow { window_id: 0 }.into(),
+            Packet102WindowClick {
+                window_id: 0,
+                slot: 9,
+                right_click: true,
+                action_number: 1,
+                item: Some(ItemStack::new(3, 32, 0)),
+            }.into(),
+            Packet102WindowClick { window_id: 0, slot: -999, right_click: false, action_number: 2, item: None }.into(),
+            Packet106Transaction { window_id: 0, action_number: 1, accepted: false }.into(),
+            Packet130UpdateSign {
+                x: 10,
+                y: 70,
+                z: -10,
+                lines: [string(""), string("Welcome"), string(""), string("")],
+            }.into(),
+            Packet255KickDisconnect { reason: string("Quitting") }.into(),
+        ];
+
+        for packet in &packets {
+            assert_round_trip(packet);
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use super::Packet;
+
+/// Sent periodically to keep the connection alive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet0KeepAlive;
+
+impl Packet for Packet0KeepAlive {
+    fn read_from<R: std::io::Read>(_input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self)
+    }
+
+    fn write_into<W: std::io::Write>(&self, _output: &mut W) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        0
+    }
+}
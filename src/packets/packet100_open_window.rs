@@ -0,0 +1,38 @@
+use byteorder::{ReadBytesExt, WriteBytesExt};
+
+use crate::mcstring::{MCString, ReadMCString, WriteMCString};
+
+use super::Packet;
+
+/// Opens a window such as a chest or furnace on the client.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet100OpenWindow {
+    pub window_id: u8,
+    pub inventory_type: u8,
+    pub title: MCString,
+    pub slots: u8,
+}
+
+impl Packet for Packet100OpenWindow {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            window_id: input.read_u8()?,
+            inventory_type: input.read_u8()?,
+            title: input.read_mcstring()?,
+            slots: input.read_u8()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_u8(self.window_id)?;
+        output.write_u8(self.inventory_type)?;
+        output.write_mcstring(&self.title)?;
+        output.write_u8(self.slots)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        3 + 2 + self.title.len()
+    }
+}
@@ -1,8 +0,0 @@
-use byteorder::{ReadBytesExt, WriteBytesExt, BE};
-use num_traits::FromPrimitive;
-
-use super::Packet;
-
-pub struct Packet101 {
-
-}
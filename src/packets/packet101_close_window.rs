@@ -0,0 +1,27 @@
+use byteorder::{ReadBytesExt, WriteBytesExt};
+
+use super::Packet;
+
+/// Closes a window, sent in both directions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet101CloseWindow {
+    pub window_id: u8,
+}
+
+impl Packet for Packet101CloseWindow {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            window_id: input.read_u8()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_u8(self.window_id)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        1
+    }
+}
@@ -0,0 +1,41 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::blocks_items::{item_stack_size, ItemStack, ReadItemStack, WriteItemStack};
+
+use super::Packet;
+
+/// Sent by the client when it clicks a slot in a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet102WindowClick {
+    pub window_id: u8,
+    pub slot: i16,
+    pub right_click: bool,
+    pub action_number: i16,
+    pub item: Option<ItemStack>,
+}
+
+impl Packet for Packet102WindowClick {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            window_id: input.read_u8()?,
+            slot: input.read_i16::<BE>()?,
+            right_click: input.read_u8()? != 0,
+            action_number: input.read_i16::<BE>()?,
+            item: input.read_item_stack()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_u8(self.window_id)?;
+        output.write_i16::<BE>(self.slot)?;
+        output.write_u8(self.right_click as u8)?;
+        output.write_i16::<BE>(self.action_number)?;
+        output.write_item_stack(&self.item)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        1 + 2 + 1 + 2 + item_stack_size(&self.item)
+    }
+}
@@ -0,0 +1,35 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::blocks_items::{item_stack_size, ItemStack, ReadItemStack, WriteItemStack};
+
+use super::Packet;
+
+/// Sets the contents of a single slot in a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet103SetSlot {
+    pub window_id: u8,
+    pub slot: i16,
+    pub item: Option<ItemStack>,
+}
+
+impl Packet for Packet103SetSlot {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            window_id: input.read_u8()?,
+            slot: input.read_i16::<BE>()?,
+            item: input.read_item_stack()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_u8(self.window_id)?;
+        output.write_i16::<BE>(self.slot)?;
+        output.write_item_stack(&self.item)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        1 + 2 + item_stack_size(&self.item)
+    }
+}
@@ -0,0 +1,43 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::blocks_items::{item_stack_size, ItemStack, ReadItemStack, WriteItemStack};
+
+use super::Packet;
+
+/// Sets the contents of every slot in a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet104WindowItems {
+    pub window_id: u8,
+    pub items: Vec<Option<ItemStack>>,
+}
+
+impl Packet for Packet104WindowItems {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        let window_id = input.read_u8()?;
+        let count = input.read_i16::<BE>()?;
+
+        let mut items = Vec::new();
+        for _ in 0..count {
+            items.push(input.read_item_stack()?);
+        }
+
+        Ok(Self {
+            window_id,
+            items,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_u8(self.window_id)?;
+        output.write_i16::<BE>(self.items.len() as i16)?;
+        for item in &self.items {
+            output.write_item_stack(item)?;
+        }
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        1 + 2 + self.items.iter().map(item_stack_size).sum::<usize>()
+    }
+}
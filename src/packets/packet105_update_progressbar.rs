@@ -0,0 +1,33 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Updates a progress bar in a window, such as a furnace's fuel and cooking progress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet105UpdateProgressbar {
+    pub window_id: u8,
+    pub progress_bar: i16,
+    pub value: i16,
+}
+
+impl Packet for Packet105UpdateProgressbar {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            window_id: input.read_u8()?,
+            progress_bar: input.read_i16::<BE>()?,
+            value: input.read_i16::<BE>()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_u8(self.window_id)?;
+        output.write_i16::<BE>(self.progress_bar)?;
+        output.write_i16::<BE>(self.value)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        5
+    }
+}
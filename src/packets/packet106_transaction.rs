@@ -0,0 +1,33 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Tells the client whether a window click was accepted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet106Transaction {
+    pub window_id: u8,
+    pub action_number: i16,
+    pub accepted: bool,
+}
+
+impl Packet for Packet106Transaction {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            window_id: input.read_u8()?,
+            action_number: input.read_i16::<BE>()?,
+            accepted: input.read_u8()? != 0,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_u8(self.window_id)?;
+        output.write_i16::<BE>(self.action_number)?;
+        output.write_u8(self.accepted as u8)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        4
+    }
+}
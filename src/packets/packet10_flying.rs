@@ -0,0 +1,27 @@
+use byteorder::{ReadBytesExt, WriteBytesExt};
+
+use super::Packet;
+
+/// Sent every tick by the client when it is neither moving nor looking around.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet10Flying {
+    pub on_ground: bool,
+}
+
+impl Packet for Packet10Flying {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            on_ground: input.read_u8()? != 0,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_u8(self.on_ground as u8)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        1
+    }
+}
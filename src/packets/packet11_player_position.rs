@@ -0,0 +1,40 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::position::PlayerPosition;
+
+use super::Packet;
+
+/// Sent by the client when it moves without looking around.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet11PlayerPosition {
+    pub position: PlayerPosition,
+    pub on_ground: bool,
+}
+
+impl Packet for Packet11PlayerPosition {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            position: PlayerPosition {
+                position_x: input.read_f64::<BE>()?,
+                position_y: input.read_f64::<BE>()?,
+                stance: input.read_f64::<BE>()?,
+                position_z: input.read_f64::<BE>()?,
+            },
+            on_ground: input.read_u8()? != 0,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_f64::<BE>(self.position.position_x)?;
+        output.write_f64::<BE>(self.position.position_y)?;
+        output.write_f64::<BE>(self.position.stance)?;
+        output.write_f64::<BE>(self.position.position_z)?;
+        output.write_u8(self.on_ground as u8)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        33
+    }
+}
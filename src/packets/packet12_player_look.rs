@@ -0,0 +1,36 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::position::PlayerLook;
+
+use super::Packet;
+
+/// Sent by the client when it looks around without moving.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet12PlayerLook {
+    pub look: PlayerLook,
+    pub on_ground: bool,
+}
+
+impl Packet for Packet12PlayerLook {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            look: PlayerLook {
+                yaw: input.read_f32::<BE>()?,
+                pitch: input.read_f32::<BE>()?,
+            },
+            on_ground: input.read_u8()? != 0,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_f32::<BE>(self.look.yaw)?;
+        output.write_f32::<BE>(self.look.pitch)?;
+        output.write_u8(self.on_ground as u8)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        9
+    }
+}
@@ -0,0 +1,45 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::mcstring::{MCString, ReadMCString, WriteMCString};
+
+use super::Packet;
+
+/// The four lines of text on a sign, sent in both directions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet130UpdateSign {
+    pub x: i32,
+    pub y: i16,
+    pub z: i32,
+    pub lines: [MCString; 4],
+}
+
+impl Packet for Packet130UpdateSign {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            x: input.read_i32::<BE>()?,
+            y: input.read_i16::<BE>()?,
+            z: input.read_i32::<BE>()?,
+            lines: [
+                input.read_mcstring()?,
+                input.read_mcstring()?,
+                input.read_mcstring()?,
+                input.read_mcstring()?,
+            ],
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.x)?;
+        output.write_i16::<BE>(self.y)?;
+        output.write_i32::<BE>(self.z)?;
+        for line in &self.lines {
+            output.write_mcstring(line)?;
+        }
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        4 + 2 + 4 + self.lines.iter().map(|l| 2 + l.len()).sum::<usize>()
+    }
+}
@@ -0,0 +1,77 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::position::{PlayerLook, PlayerPosition, PlayerPositionLook};
+
+use super::Packet;
+
+/// Combined position and look of a player.
+///
+/// When sent by the server the `position_y` and `stance` fields trade places on
+/// the wire: the client reads the first one as its eye height.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet13PlayerLookMove {
+    pub position_look: PlayerPositionLook,
+    pub on_ground: bool,
+}
+
+impl Packet13PlayerLookMove {
+    /// Create the packet the server sends to move a player, with the eye
+    /// height written where the client expects it.
+    pub fn new_clientbound(position_look: PlayerPositionLook) -> Self {
+        let mut position_look = position_look;
+        let position = &mut position_look.position;
+        std::mem::swap(&mut position.position_y, &mut position.stance);
+
+        Self {
+            position_look,
+            on_ground: true,
+        }
+    }
+}
+
+impl Packet for Packet13PlayerLookMove {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        let position_x = input.read_f64::<BE>()?;
+        let position_y = input.read_f64::<BE>()?;
+        let stance = input.read_f64::<BE>()?;
+        let position_z = input.read_f64::<BE>()?;
+
+        let yaw = input.read_f32::<BE>()?;
+        let pitch = input.read_f32::<BE>()?;
+
+        let on_ground = input.read_u8()? != 0;
+
+        Ok(Self {
+            position_look: PlayerPositionLook {
+                position: PlayerPosition {
+                    position_x,
+                    position_y,
+                    stance,
+                    position_z,
+                },
+                look: PlayerLook {
+                    yaw,
+                    pitch,
+                },
+            },
+            on_ground,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        let PlayerPositionLook { position, look } = self.position_look;
+        output.write_f64::<BE>(position.position_x)?;
+        output.write_f64::<BE>(position.position_y)?;
+        output.write_f64::<BE>(position.stance)?;
+        output.write_f64::<BE>(position.position_z)?;
+        output.write_f32::<BE>(look.yaw)?;
+        output.write_f32::<BE>(look.pitch)?;
+        output.write_u8(self.on_ground as u8)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        41
+    }
+}
@@ -0,0 +1,54 @@
+use std::io;
+
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+use num_traits::FromPrimitive;
+
+use crate::player::{DiggingStatus, Direction};
+
+use super::Packet;
+
+/// Sent by the client while it is digging a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet14BlockDig {
+    pub status: DiggingStatus,
+    pub x: i32,
+    pub y: u8,
+    pub z: i32,
+    pub face: Direction,
+}
+
+impl Packet for Packet14BlockDig {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        let status = input.read_u8()?;
+        let status = DiggingStatus::from_u8(status)
+            .ok_or(io::Error::new(io::ErrorKind::InvalidData, format!("invalid digging status {status}")))?;
+        let x = input.read_i32::<BE>()?;
+        let y = input.read_u8()?;
+        let z = input.read_i32::<BE>()?;
+        let face = input.read_u8()?;
+        let face = Direction::from_u8(face)
+            .ok_or(io::Error::new(io::ErrorKind::InvalidData, format!("invalid block face {face}")))?;
+
+        Ok(Self {
+            status,
+            x,
+            y,
+            z,
+            face,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_u8(self.status as u8)?;
+        output.write_i32::<BE>(self.x)?;
+        output.write_u8(self.y)?;
+        output.write_i32::<BE>(self.z)?;
+        output.write_u8(self.face as u8)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        11
+    }
+}
@@ -1,40 +1,52 @@
-use crate::{blocks_items::{BlockItem, BlockItemID, ItemStack}, player::Direction};
+use crate::blocks_items::{BlockItem, BlockItemID};
 
 use byteorder::{ReadBytesExt, WriteBytesExt, BE};
-use num_traits::FromPrimitive;
 
 use super::Packet;
 
-#[derive(Debug, Clone, Copy)]
+/// Sent by the client when it places a block or uses an item.
+///
+/// A `direction` of `255` means the item was used without targeting a block.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Packet15Place {
-    id: BlockItem,
-    x_position: i32,
-    y_position: u8,
-    z_position: i32,
-    direction: u8,
-    amount: Option<u8>,
-    health: Option<i16>,
+    pub id: BlockItem,
+    pub x_position: i32,
+    pub y_position: u8,
+    pub z_position: i32,
+    pub direction: u8,
+    pub amount: Option<u8>,
+    pub health: Option<i16>,
 }
 
 impl Packet for Packet15Place {
     fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
-        let id = BlockItem::from_id(input.read_i16::<BE>()?);
+        let id = input.read_i16::<BE>()?;
         Ok(Self {
-            id,
+            id: BlockItem::from_id(id),
             x_position: input.read_i32::<BE>()?,
             y_position: input.read_u8()?,
             z_position: input.read_i32::<BE>()?,
             direction: input.read_u8()?,
-            amount: if id.id() <= 0 { None } else { Some(input.read_u8()?) },
-            health: if id.id() <= 0 { None } else { Some(input.read_i16::<BE>()?) }
+            amount: if id < 0 { None } else { Some(input.read_u8()?) },
+            health: if id < 0 { None } else { Some(input.read_i16::<BE>()?) }
         })
     }
 
     fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
-        unimplemented!()
+        output.write_i16::<BE>(self.id.id())?;
+        output.write_i32::<BE>(self.x_position)?;
+        output.write_u8(self.y_position)?;
+        output.write_i32::<BE>(self.z_position)?;
+        output.write_u8(self.direction)?;
+        if self.id.id() >= 0 {
+            output.write_u8(self.amount.unwrap_or(0))?;
+            output.write_i16::<BE>(self.health.unwrap_or(0))?;
+        }
+
+        Ok(())
     }
 
     fn size(&self) -> usize {
-        todo!()
+        if self.id.id() >= 0 { 15 } else { 12 }
     }
 }
@@ -0,0 +1,34 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::blocks_items::{BlockItem, BlockItemID};
+
+use super::Packet;
+
+/// Sent by the client when it changes the item in its hand.
+///
+/// The server sends the same packet to show what another player is holding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet16BlockItemSwitch {
+    pub entity_id: i32,
+    pub item: BlockItem,
+}
+
+impl Packet for Packet16BlockItemSwitch {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            item: BlockItem::from_id(input.read_i16::<BE>()?),
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_i16::<BE>(self.item.id())?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        6
+    }
+}
@@ -0,0 +1,35 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::blocks_items::{BlockItemID, ItemStack};
+
+use super::Packet;
+
+/// Gives the player an item, which the client puts into the first free slot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet17AddToInventory {
+    pub item: ItemStack,
+}
+
+impl Packet for Packet17AddToInventory {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        let item_id = input.read_i16::<BE>()?;
+        let stack_size = input.read_u8()?;
+        let item_damage = input.read_i16::<BE>()?;
+
+        Ok(Self {
+            item: ItemStack::new(item_id as i32, stack_size as i32, item_damage as i32),
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i16::<BE>(self.item.item_id.id())?;
+        output.write_u8(self.item.stack_size as u8)?;
+        output.write_i16::<BE>(self.item.item_damage as i16)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        5
+    }
+}
@@ -0,0 +1,30 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Plays an animation on an entity, such as swinging an arm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet18ArmAnimation {
+    pub entity_id: i32,
+    pub animation: u8,
+}
+
+impl Packet for Packet18ArmAnimation {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            animation: input.read_u8()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_u8(self.animation)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        5
+    }
+}
@@ -4,7 +4,10 @@ use crate::mcstring::{MCString, WriteMCString, ReadMCString};
 
 use super::Packet;
 
-#[derive(Debug, Clone)]
+/// Sent by the client to log in, and by the server to accept the login.
+///
+/// The server sends the player's entity ID in the `protocol_version` field.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Packet1Login {
     pub username: MCString,
     pub password: MCString,
@@ -28,8 +31,8 @@ impl Packet1Login {
 impl Packet for Packet1Login {
     fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
         let protocol_version = input.read_i32::<BE>()?;
-        let username = input.read_mcstring()?.into();
-        let password = input.read_mcstring()?.into();
+        let username = input.read_mcstring()?;
+        let password = input.read_mcstring()?;
         let world_seed = input.read_i64::<BE>()?;
         let dimension = input.read_i8()?;
 
@@ -43,16 +46,16 @@ impl Packet for Packet1Login {
     }
 
     fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
-        output.write_i32::<BE>(self.protocol_version).unwrap();
-        output.write_mcstring(&self.username).unwrap();
-        output.write_mcstring(&self.password).unwrap();
-        output.write_i64::<BE>(self.world_seed).unwrap();
-        output.write_i8(self.dimension).unwrap();
+        output.write_i32::<BE>(self.protocol_version)?;
+        output.write_mcstring(&self.username)?;
+        output.write_mcstring(&self.password)?;
+        output.write_i64::<BE>(self.world_seed)?;
+        output.write_i8(self.dimension)?;
 
         Ok(())
     }
 
     fn size(&self) -> usize {
-        4 + self.username.len() + self.password.len() + 8 + 1
+        4 + 2 + self.username.len() + 2 + self.password.len() + 8 + 1
     }
 }
@@ -0,0 +1,52 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::mcstring::{MCString, ReadMCString, WriteMCString};
+
+use super::Packet;
+
+/// Spawns another player for the client.
+///
+/// Positions are absolute integers, the block position multiplied by 32.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet20NamedEntitySpawn {
+    pub entity_id: i32,
+    pub name: MCString,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub rotation: i8,
+    pub pitch: i8,
+    pub current_item: i16,
+}
+
+impl Packet for Packet20NamedEntitySpawn {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            name: input.read_mcstring()?,
+            x: input.read_i32::<BE>()?,
+            y: input.read_i32::<BE>()?,
+            z: input.read_i32::<BE>()?,
+            rotation: input.read_i8()?,
+            pitch: input.read_i8()?,
+            current_item: input.read_i16::<BE>()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_mcstring(&self.name)?;
+        output.write_i32::<BE>(self.x)?;
+        output.write_i32::<BE>(self.y)?;
+        output.write_i32::<BE>(self.z)?;
+        output.write_i8(self.rotation)?;
+        output.write_i8(self.pitch)?;
+        output.write_i16::<BE>(self.current_item)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        20 + 2 + self.name.len()
+    }
+}
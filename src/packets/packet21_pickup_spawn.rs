@@ -0,0 +1,51 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Spawns a dropped item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet21PickupSpawn {
+    pub entity_id: i32,
+    pub item_id: i16,
+    pub count: u8,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub rotation: i8,
+    pub pitch: i8,
+    pub roll: i8,
+}
+
+impl Packet for Packet21PickupSpawn {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            item_id: input.read_i16::<BE>()?,
+            count: input.read_u8()?,
+            x: input.read_i32::<BE>()?,
+            y: input.read_i32::<BE>()?,
+            z: input.read_i32::<BE>()?,
+            rotation: input.read_i8()?,
+            pitch: input.read_i8()?,
+            roll: input.read_i8()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_i16::<BE>(self.item_id)?;
+        output.write_u8(self.count)?;
+        output.write_i32::<BE>(self.x)?;
+        output.write_i32::<BE>(self.y)?;
+        output.write_i32::<BE>(self.z)?;
+        output.write_i8(self.rotation)?;
+        output.write_i8(self.pitch)?;
+        output.write_i8(self.roll)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        22
+    }
+}
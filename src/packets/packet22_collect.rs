@@ -0,0 +1,30 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Plays the pickup animation of an item being collected by an entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet22Collect {
+    pub collected_entity_id: i32,
+    pub collector_entity_id: i32,
+}
+
+impl Packet for Packet22Collect {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            collected_entity_id: input.read_i32::<BE>()?,
+            collector_entity_id: input.read_i32::<BE>()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.collected_entity_id)?;
+        output.write_i32::<BE>(self.collector_entity_id)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        8
+    }
+}
@@ -0,0 +1,39 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Spawns an object or vehicle, such as a boat or minecart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet23VehicleSpawn {
+    pub entity_id: i32,
+    pub object_type: u8,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Packet for Packet23VehicleSpawn {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            object_type: input.read_u8()?,
+            x: input.read_i32::<BE>()?,
+            y: input.read_i32::<BE>()?,
+            z: input.read_i32::<BE>()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_u8(self.object_type)?;
+        output.write_i32::<BE>(self.x)?;
+        output.write_i32::<BE>(self.y)?;
+        output.write_i32::<BE>(self.z)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        17
+    }
+}
@@ -0,0 +1,27 @@
+use crate::mcstring::{MCString, ReadMCString, WriteMCString};
+
+use super::Packet;
+
+/// Disconnects a client, with a reason. Sent in both directions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet255KickDisconnect {
+    pub reason: MCString,
+}
+
+impl Packet for Packet255KickDisconnect {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            reason: input.read_mcstring()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_mcstring(&self.reason)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        2 + self.reason.len()
+    }
+}
@@ -0,0 +1,36 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Sets the velocity of an entity, in units of 1/8000 of a block per tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet28EntityVelocity {
+    pub entity_id: i32,
+    pub motion_x: i16,
+    pub motion_y: i16,
+    pub motion_z: i16,
+}
+
+impl Packet for Packet28EntityVelocity {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            motion_x: input.read_i16::<BE>()?,
+            motion_y: input.read_i16::<BE>()?,
+            motion_z: input.read_i16::<BE>()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_i16::<BE>(self.motion_x)?;
+        output.write_i16::<BE>(self.motion_y)?;
+        output.write_i16::<BE>(self.motion_z)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        10
+    }
+}
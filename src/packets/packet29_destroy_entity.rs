@@ -0,0 +1,27 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Removes an entity from the client.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet29DestroyEntity {
+    pub entity_id: i32,
+}
+
+impl Packet for Packet29DestroyEntity {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        4
+    }
+}
@@ -0,0 +1,30 @@
+use crate::mcstring::{MCString, ReadMCString, WriteMCString};
+
+use super::Packet;
+
+/// The first packet sent by a client.
+///
+/// The client sends its username, and the server replies with a connection
+/// hash, or `-` if no authentication is required.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet2Handshake {
+    pub username: MCString,
+}
+
+impl Packet for Packet2Handshake {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            username: input.read_mcstring()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_mcstring(&self.username)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        2 + self.username.len()
+    }
+}
@@ -0,0 +1,36 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Moves an entity by less than 4 blocks, in units of 1/32 of a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet31RelEntityMove {
+    pub entity_id: i32,
+    pub x: i8,
+    pub y: i8,
+    pub z: i8,
+}
+
+impl Packet for Packet31RelEntityMove {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            x: input.read_i8()?,
+            y: input.read_i8()?,
+            z: input.read_i8()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_i8(self.x)?;
+        output.write_i8(self.y)?;
+        output.write_i8(self.z)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        7
+    }
+}
@@ -0,0 +1,33 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Rotates an entity. Angles are in steps of 1/256 of a full turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet32EntityLook {
+    pub entity_id: i32,
+    pub yaw: i8,
+    pub pitch: i8,
+}
+
+impl Packet for Packet32EntityLook {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            yaw: input.read_i8()?,
+            pitch: input.read_i8()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_i8(self.yaw)?;
+        output.write_i8(self.pitch)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        6
+    }
+}
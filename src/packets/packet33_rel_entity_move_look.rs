@@ -0,0 +1,45 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Combination of [`Packet31RelEntityMove`] and [`Packet32EntityLook`].
+///
+/// [`Packet31RelEntityMove`]: super::packet31_rel_entity_move::Packet31RelEntityMove
+/// [`Packet32EntityLook`]: super::packet32_entity_look::Packet32EntityLook
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet33RelEntityMoveLook {
+    pub entity_id: i32,
+    pub x: i8,
+    pub y: i8,
+    pub z: i8,
+    pub yaw: i8,
+    pub pitch: i8,
+}
+
+impl Packet for Packet33RelEntityMoveLook {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            x: input.read_i8()?,
+            y: input.read_i8()?,
+            z: input.read_i8()?,
+            yaw: input.read_i8()?,
+            pitch: input.read_i8()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_i8(self.x)?;
+        output.write_i8(self.y)?;
+        output.write_i8(self.z)?;
+        output.write_i8(self.yaw)?;
+        output.write_i8(self.pitch)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        9
+    }
+}
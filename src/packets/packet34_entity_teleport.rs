@@ -0,0 +1,42 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Moves an entity to an absolute position, the block position multiplied by 32.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet34EntityTeleport {
+    pub entity_id: i32,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub yaw: i8,
+    pub pitch: i8,
+}
+
+impl Packet for Packet34EntityTeleport {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            x: input.read_i32::<BE>()?,
+            y: input.read_i32::<BE>()?,
+            z: input.read_i32::<BE>()?,
+            yaw: input.read_i8()?,
+            pitch: input.read_i8()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_i32::<BE>(self.x)?;
+        output.write_i32::<BE>(self.y)?;
+        output.write_i32::<BE>(self.z)?;
+        output.write_i8(self.yaw)?;
+        output.write_i8(self.pitch)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        18
+    }
+}
@@ -0,0 +1,30 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Plays a status effect on an entity, such as being hurt or dying.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet38EntityStatus {
+    pub entity_id: i32,
+    pub status: i8,
+}
+
+impl Packet for Packet38EntityStatus {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            status: input.read_i8()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_i8(self.status)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        5
+    }
+}
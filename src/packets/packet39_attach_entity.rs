@@ -0,0 +1,30 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Attaches an entity to a vehicle, or detaches it if the vehicle ID is `-1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet39AttachEntity {
+    pub entity_id: i32,
+    pub vehicle_id: i32,
+}
+
+impl Packet for Packet39AttachEntity {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            entity_id: input.read_i32::<BE>()?,
+            vehicle_id: input.read_i32::<BE>()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.entity_id)?;
+        output.write_i32::<BE>(self.vehicle_id)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        8
+    }
+}
@@ -0,0 +1,27 @@
+use crate::mcstring::{MCString, ReadMCString, WriteMCString};
+
+use super::Packet;
+
+/// A chat message, sent in both directions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet3Chat {
+    pub message: MCString,
+}
+
+impl Packet for Packet3Chat {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            message: input.read_mcstring()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_mcstring(&self.message)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        2 + self.message.len()
+    }
+}
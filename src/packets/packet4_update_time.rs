@@ -0,0 +1,27 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// The current world time in ticks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet4UpdateTime {
+    pub time: i64,
+}
+
+impl Packet for Packet4UpdateTime {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            time: input.read_i64::<BE>()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i64::<BE>(self.time)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        8
+    }
+}
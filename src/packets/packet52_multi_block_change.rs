@@ -0,0 +1,55 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Changes several blocks within one chunk at once.
+///
+/// Each coordinate packs the position within the chunk as `x << 12 | z << 8 | y`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet52MultiBlockChange {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    pub coordinates: Vec<i16>,
+    pub types: Vec<u8>,
+    pub metadata: Vec<u8>,
+}
+
+impl Packet for Packet52MultiBlockChange {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        let chunk_x = input.read_i32::<BE>()?;
+        let chunk_z = input.read_i32::<BE>()?;
+        let size = input.read_i16::<BE>()?.max(0) as usize;
+
+        let mut coordinates = vec![0; size];
+        input.read_i16_into::<BE>(&mut coordinates)?;
+        let mut types = vec![0; size];
+        input.read_exact(&mut types)?;
+        let mut metadata = vec![0; size];
+        input.read_exact(&mut metadata)?;
+
+        Ok(Self {
+            chunk_x,
+            chunk_z,
+            coordinates,
+            types,
+            metadata,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.chunk_x)?;
+        output.write_i32::<BE>(self.chunk_z)?;
+        output.write_i16::<BE>(self.coordinates.len() as i16)?;
+        for coordinate in &self.coordinates {
+            output.write_i16::<BE>(*coordinate)?;
+        }
+        output.write_all(&self.types)?;
+        output.write_all(&self.metadata)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        4 + 4 + 2 + self.coordinates.len() * 4
+    }
+}
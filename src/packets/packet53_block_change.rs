@@ -0,0 +1,39 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Changes a single block in the world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet53BlockChange {
+    pub x: i32,
+    pub y: u8,
+    pub z: i32,
+    pub block_type: u8,
+    pub metadata: u8,
+}
+
+impl Packet for Packet53BlockChange {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            x: input.read_i32::<BE>()?,
+            y: input.read_u8()?,
+            z: input.read_i32::<BE>()?,
+            block_type: input.read_u8()?,
+            metadata: input.read_u8()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.x)?;
+        output.write_u8(self.y)?;
+        output.write_i32::<BE>(self.z)?;
+        output.write_u8(self.block_type)?;
+        output.write_u8(self.metadata)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        11
+    }
+}
@@ -0,0 +1,45 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// The data of a tile entity such as a sign or chest, as gzipped NBT.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet59ComplexEntity {
+    pub x: i32,
+    pub y: i16,
+    pub z: i32,
+    pub data: Vec<u8>,
+}
+
+impl Packet for Packet59ComplexEntity {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        let x = input.read_i32::<BE>()?;
+        let y = input.read_i16::<BE>()?;
+        let z = input.read_i32::<BE>()?;
+        let length = input.read_u16::<BE>()?;
+
+        let mut data = vec![0; length as usize];
+        input.read_exact(&mut data)?;
+
+        Ok(Self {
+            x,
+            y,
+            z,
+            data,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.x)?;
+        output.write_i16::<BE>(self.y)?;
+        output.write_i32::<BE>(self.z)?;
+        output.write_u16::<BE>(self.data.len() as u16)?;
+        output.write_all(&self.data)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        4 + 2 + 4 + 2 + self.data.len()
+    }
+}
@@ -0,0 +1,46 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use crate::blocks_items::{item_stack_size, ItemStack, ReadItemStack, WriteItemStack};
+
+use super::Packet;
+
+/// The contents of one section of the player's inventory.
+///
+/// The `inventory_type` is `-1` for the main inventory, `-2` for the crafting
+/// grid and `-3` for the armor slots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet5PlayerInventory {
+    pub inventory_type: i32,
+    pub items: Vec<Option<ItemStack>>,
+}
+
+impl Packet for Packet5PlayerInventory {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        let inventory_type = input.read_i32::<BE>()?;
+        let count = input.read_i16::<BE>()?;
+
+        let mut items = Vec::new();
+        for _ in 0..count {
+            items.push(input.read_item_stack()?);
+        }
+
+        Ok(Self {
+            inventory_type,
+            items,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.inventory_type)?;
+        output.write_i16::<BE>(self.items.len() as i16)?;
+        for item in &self.items {
+            output.write_item_stack(item)?;
+        }
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        4 + 2 + self.items.iter().map(item_stack_size).sum::<usize>()
+    }
+}
@@ -0,0 +1,55 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// An explosion, with the offsets of every block it destroyed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet60Explosion {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub size: f32,
+    pub destroyed_blocks: Vec<(i8, i8, i8)>,
+}
+
+impl Packet for Packet60Explosion {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        let x = input.read_f64::<BE>()?;
+        let y = input.read_f64::<BE>()?;
+        let z = input.read_f64::<BE>()?;
+        let size = input.read_f32::<BE>()?;
+        let count = input.read_i32::<BE>()?;
+
+        let mut destroyed_blocks = Vec::new();
+        for _ in 0..count {
+            destroyed_blocks.push((input.read_i8()?, input.read_i8()?, input.read_i8()?));
+        }
+
+        Ok(Self {
+            x,
+            y,
+            z,
+            size,
+            destroyed_blocks,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_f64::<BE>(self.x)?;
+        output.write_f64::<BE>(self.y)?;
+        output.write_f64::<BE>(self.z)?;
+        output.write_f32::<BE>(self.size)?;
+        output.write_i32::<BE>(self.destroyed_blocks.len() as i32)?;
+        for (x, y, z) in &self.destroyed_blocks {
+            output.write_i8(*x)?;
+            output.write_i8(*y)?;
+            output.write_i8(*z)?;
+        }
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        8 * 3 + 4 + 4 + self.destroyed_blocks.len() * 3
+    }
+}
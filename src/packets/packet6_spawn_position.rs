@@ -0,0 +1,33 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// The position the compass points to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet6SpawnPosition {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Packet for Packet6SpawnPosition {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            x: input.read_i32::<BE>()?,
+            y: input.read_i32::<BE>()?,
+            z: input.read_i32::<BE>()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.x)?;
+        output.write_i32::<BE>(self.y)?;
+        output.write_i32::<BE>(self.z)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        12
+    }
+}
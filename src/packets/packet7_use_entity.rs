@@ -0,0 +1,33 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Sent when a player attacks or right clicks an entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet7UseEntity {
+    pub player_id: i32,
+    pub target_id: i32,
+    pub left_click: bool,
+}
+
+impl Packet for Packet7UseEntity {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            player_id: input.read_i32::<BE>()?,
+            target_id: input.read_i32::<BE>()?,
+            left_click: input.read_u8()? != 0,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i32::<BE>(self.player_id)?;
+        output.write_i32::<BE>(self.target_id)?;
+        output.write_u8(self.left_click as u8)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        9
+    }
+}
@@ -0,0 +1,27 @@
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
+
+use super::Packet;
+
+/// Sets the health of the player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet8UpdateHealth {
+    pub health: i16,
+}
+
+impl Packet for Packet8UpdateHealth {
+    fn read_from<R: std::io::Read>(input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self {
+            health: input.read_i16::<BE>()?,
+        })
+    }
+
+    fn write_into<W: std::io::Write>(&self, output: &mut W) -> Result<(), std::io::Error> {
+        output.write_i16::<BE>(self.health)?;
+
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        2
+    }
+}
@@ -0,0 +1,19 @@
+use super::Packet;
+
+/// Sent by the client to respawn after dying, and echoed back by the server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Packet9Respawn;
+
+impl Packet for Packet9Respawn {
+    fn read_from<R: std::io::Read>(_input: &mut R) -> Result<Self, std::io::Error> {
+        Ok(Self)
+    }
+
+    fn write_into<W: std::io::Write>(&self, _output: &mut W) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    fn size(&self) -> usize {
+        0
+    }
+}
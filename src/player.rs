@@ -1,7 +1,7 @@
 use num_derive::FromPrimitive;
 
 #[repr(u8)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[derive(FromPrimitive)]
 pub enum DiggingStatus {
     StartedDigging = 0,
@@ -12,7 +12,7 @@ pub enum DiggingStatus {
 
 /// The face of a block, a direction.
 #[repr(u8)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[derive(FromPrimitive)]
 pub enum Direction {
     NegY = 0,
@@ -23,11 +23,16 @@ pub enum Direction {
     PosX = 5,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct PlayerDigging {
-    status: DiggingStatus,
-    position_x: i32,
-    position_y: u8,
-    position_z: i32,
-    face: Direction,
+impl Direction {
+    /// The offset from a block to its neighbour on this face.
+    pub fn offset(&self) -> (i32, i32, i32) {
+        match self {
+            Direction::NegY => (0, -1, 0),
+            Direction::PosY => (0, 1, 0),
+            Direction::NegZ => (0, 0, -1),
+            Direction::PosZ => (0, 0, 1),
+            Direction::NegX => (-1, 0, 0),
+            Direction::PosX => (1, 0, 0),
+        }
+    }
 }
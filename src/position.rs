@@ -1,59 +1,9 @@
-use std::io::Read;
-
-use byteorder::{ReadBytesExt, WriteBytesExt, BE};
-
-use crate::byte_ops::ToBytes;
-
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct PlayerPositionLook {
     pub position: PlayerPosition,
     pub look: PlayerLook,
 }
 
-impl ToBytes for PlayerPositionLook {
-    type Bytes = Vec<u8>;
-
-    fn to_bytes(self) -> Self::Bytes {
-        let mut out_buf = Vec::new();
-        out_buf.write_f64::<BE>(self.position.position_x).unwrap();
-        out_buf.write_f64::<BE>(self.position.position_y).unwrap();
-        out_buf.write_f64::<BE>(self.position.stance).unwrap();
-        out_buf.write_f64::<BE>(self.position.position_z).unwrap();
-        out_buf.write_f32::<BE>(self.look.yaw).unwrap();
-        out_buf.write_f32::<BE>(self.look.pitch).unwrap();
-        out_buf.write_u8(true as u8).unwrap();
-
-        out_buf
-    }
-}
-
-impl PlayerPositionLook {
-    pub fn from_bytes<R: Read>(stream: &mut R) -> Self {
-        let position_x = stream.read_f64::<BE>().unwrap();
-        let position_y = stream.read_f64::<BE>().unwrap();
-        let stance = stream.read_f64::<BE>().unwrap();
-        let position_z = stream.read_f64::<BE>().unwrap();
-
-        let yaw = stream.read_f32::<BE>().unwrap();
-        let pitch = stream.read_f32::<BE>().unwrap();
-
-        let _on_ground = stream.read_u8().unwrap();
-
-        Self {
-            position: PlayerPosition {
-                position_x,
-                stance,
-                position_y,
-                position_z,
-            },
-            look: PlayerLook {
-                yaw,
-                pitch,
-            },
-        }
-    }
-}
-
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct PlayerPosition {
     pub position_x: f64,
@@ -62,68 +12,36 @@ pub struct PlayerPosition {
     pub position_z: f64,
 }
 
-impl ToBytes for PlayerPosition {
-    type Bytes = Vec<u8>;
-
-    fn to_bytes(self) -> Self::Bytes {
-        let mut out_buf = Vec::new();
-        out_buf.write_f64::<BE>(self.position_x).unwrap();
-        out_buf.write_f64::<BE>(self.position_y).unwrap();
-        out_buf.write_f64::<BE>(self.stance).unwrap();
-        out_buf.write_f64::<BE>(self.position_z).unwrap();
-        out_buf.write_u8(1).unwrap();
-
-        out_buf
-    }
-}
-
-impl PlayerPosition {
-    pub fn from_bytes<R: Read>(stream: &mut R) -> Self {
-        let position_x = stream.read_f64::<BE>().unwrap();
-        let position_y = stream.read_f64::<BE>().unwrap();
-        let stance = stream.read_f64::<BE>().unwrap();
-        let position_z = stream.read_f64::<BE>().unwrap();
-
-        let _on_ground = stream.read_u8().unwrap() != 0;
-
-        Self {
-            position_x,
-            stance,
-            position_y,
-            position_z,
-        }
-    }
-}
-
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct PlayerLook {
     pub yaw: f32,
     pub pitch: f32,
 }
 
-impl ToBytes for PlayerLook {
-    type Bytes = Vec<u8>;
+/// The height of a player's eyes above their feet.
+pub const PLAYER_EYE_HEIGHT: f64 = 1.62;
 
-    fn to_bytes(self) -> Self::Bytes {
-        let mut out_buf = Vec::new();
-        out_buf.write_f32::<BE>(self.yaw).unwrap();
-        out_buf.write_f32::<BE>(self.pitch).unwrap();
-        out_buf.write_u8(1).unwrap();
-
-        out_buf
-    }
+/// The position of an entity as it is sent to clients.
+///
+/// Coordinates are fixed point in 1/32 of a block, and angles are in steps of
+/// 1/256 of a full turn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AbsolutePosition {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub yaw: i8,
+    pub pitch: i8,
 }
 
-impl PlayerLook {
-    pub fn from_bytes<R: Read>(stream: &mut R) -> Self {
-        let yaw = stream.read_f32::<BE>().unwrap();
-        let pitch = stream.read_f32::<BE>().unwrap();
-
-        let _on_ground = stream.read_u8().unwrap() != 0;
-
+impl From<&PlayerPositionLook> for AbsolutePosition {
+    fn from(value: &PlayerPositionLook) -> Self {
         Self {
-            yaw,
-            pitch,
+            x: (value.position.position_x * 32.0).floor() as i32,
+            y: (value.position.position_y * 32.0).floor() as i32,
+            z: (value.position.position_z * 32.0).floor() as i32,
+            yaw: (value.look.yaw * 256.0 / 360.0) as i32 as i8,
+            pitch: (value.look.pitch * 256.0 / 360.0) as i32 as i8,
         }
     }
 }
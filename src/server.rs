@@ -0,0 +1,498 @@
+use std::{
+    sync::mpsc::{Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use log::{debug, error, info, warn};
+use num_traits::FromPrimitive;
+use rand::Rng;
+
+use crate::{
+    blocks_items::{Block, BlockItem, BlockItemID},
+    commands::{self, CommandSender},
+    connection::Connection,
+    inventory::InventorySection,
+    packets::{
+        packet0_keep_alive::Packet0KeepAlive,
+        packet13_player_look_move::Packet13PlayerLookMove,
+        packet14_block_dig::Packet14BlockDig,
+        packet15_place::Packet15Place,
+        packet16_block_item_switch::Packet16BlockItemSwitch,
+        packet5_player_inventory::Packet5PlayerInventory,
+        packet1_login::Packet1Login,
+        packet29_destroy_entity::Packet29DestroyEntity,
+        packet4_update_time::Packet4UpdateTime,
+        packet8_update_health::Packet8UpdateHealth,
+        ServerboundPacket,
+    },
+    player::{DiggingStatus, Direction},
+    position::{PlayerPosition, PLAYER_EYE_HEIGHT},
+    state::{GameState, PlayerState, TICK_TIME_SAMPLES},
+};
+
+/// The number of ticks the server tries to run every second.
+pub const TICKS_PER_SECOND: u32 = 20;
+
+/// The time a single tick should take.
+pub const TICK_LENGTH: Duration = Duration::from_millis(1000 / TICKS_PER_SECOND as u64);
+
+/// How far behind the server may fall before it gives up on catching up.
+const MAX_TICK_LAG: Duration = Duration::from_secs(2);
+
+/// The longest chat message a client is allowed to send.
+pub const MAX_CHAT_LENGTH: usize = 119;
+
+/// How far from where a player would spawn to look for somewhere safe to
+/// stand, in blocks.
+const SAFE_SPAWN_SEARCH_RADIUS: i32 = 16;
+
+/// How often players are sent a keep-alive, in ticks.
+const KEEP_ALIVE_INTERVAL: u64 = 20;
+
+/// How far from their eyes a player can break or place blocks.
+const MAX_REACH: f64 = 8.0;
+
+/// Something that happened on a connection, queued for the server thread.
+#[derive(Debug)]
+pub enum ServerEvent {
+    /// A player finished logging in
+    Join {
+        player: Box<PlayerState>,
+        connection: Connection,
+    },
+
+    /// A packet was received from a player
+    Packet {
+        eid: i32,
+        packet: ServerboundPacket,
+    },
+
+    /// The connection to a player was closed
+    Disconnected {
+        eid: i32,
+        reason: String,
+    },
+
+    /// A command was typed into the server console
+    Console(String),
+
+    /// The server should save everything and stop
+    Shutdown,
+}
+
+/// Run the game at [`TICKS_PER_SECOND`] until every event sender is gone.
+pub fn tick_loop(mut game_state: GameState, events: Receiver<ServerEvent>) {
+    let mut next_tick = Instant::now();
+    loop {
+        let tick_start = Instant::now();
+
+        loop {
+            match events.try_recv() {
+                Ok(ServerEvent::Shutdown) | Err(TryRecvError::Disconnected) => {
+                    shutdown(&mut game_state);
+                    return
+                }
+                Ok(event) => handle_event(&mut game_state, event),
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        if game_state.is_stopping() {
+            shutdown(&mut game_state);
+            return
+        }
+
+        game_state.tick();
+        keep_alive(&mut game_state);
+        game_state.flush_connections();
+
+        let tick_time = tick_start.elapsed();
+        game_state.record_tick_time(tick_time);
+        if tick_time > TICK_LENGTH {
+            debug!("Tick {} took {}ms", game_state.tick_count(), tick_time.as_millis());
+        }
+
+        // Single slow ticks are normal, but being slow on average means the
+        // game is running slower than it should
+        if game_state.tick_count().is_multiple_of(TICK_TIME_SAMPLES as u64) {
+            let average = game_state.average_tick_time();
+            if average > TICK_LENGTH {
+                warn!(
+                    "The last {TICK_TIME_SAMPLES} ticks took {}ms on average, more than the {}ms they should",
+                    average.as_millis(),
+                    TICK_LENGTH.as_millis(),
+                );
+            }
+        }
+
+        next_tick += TICK_LENGTH;
+        let now = Instant::now();
+        if next_tick > now {
+            thread::sleep(next_tick - now);
+        } else if now - next_tick > MAX_TICK_LAG {
+            let behind = now - next_tick;
+            warn!(
+                "Can't keep up! Did the system time change, or is the server overloaded? Running {}ms behind, skipping {} tick(s)",
+                behind.as_millis(),
+                behind.as_millis() / TICK_LENGTH.as_millis(),
+            );
+            next_tick = now;
+        }
+    }
+}
+
+/// Send keep-alives on schedule and disconnect players who have sent nothing
+/// for longer than the timeout.
+///
+/// Keep-alives in this version carry no ID and the Beta client never answers
+/// them, or anything else the server sends, so there is no way to measure a
+/// player's latency.
+fn keep_alive(game_state: &mut GameState) {
+    let tick = game_state.tick_count();
+    let timeout = game_state.config().connection_timeout;
+
+    let timed_out: Vec<_> = game_state.player_list()
+        .values()
+        .filter(|p| tick.saturating_sub(p.last_packet_tick()) > timeout)
+        .map(|p| p.eid())
+        .collect();
+    for eid in timed_out {
+        if let Some(player) = game_state.player_by_eid(eid) {
+            info!("{} lost connection: Timed out", player.username());
+        }
+        if let Some(c) = game_state.connections_mut().get_mut(&eid) {
+            c.kick("Timed out");
+        }
+        remove_player(game_state, eid);
+    }
+
+    if tick.is_multiple_of(KEEP_ALIVE_INTERVAL) {
+        game_state.broadcast(Packet0KeepAlive);
+    }
+}
+
+/// Disconnect every player and save the world.
+fn shutdown(game_state: &mut GameState) {
+    info!("Stopping server");
+    for connection in game_state.connections_mut().values_mut() {
+        connection.kick("Server closed");
+    }
+
+    info!("Saving chunks");
+    game_state.save();
+}
+
+/// Send the spawn point and their position to a player who just joined.
+/// The chunks around them are sent over the next ticks.
+fn send_world(game_state: &mut GameState, player: &PlayerState) {
+    let eid = player.eid();
+    let packet = game_state.spawn_point_packet();
+    game_state.send_to(eid, packet);
+    let time = game_state.world_time();
+    game_state.send_to(eid, Packet4UpdateTime { time });
+    game_state.send_to(eid, Packet8UpdateHealth { health: player.health() });
+    for section in InventorySection::ALL {
+        game_state.send_to(eid, player.inventory().packet(section));
+    }
+    game_state.send_to(eid, Packet13PlayerLookMove::new_clientbound(*player.position_look()));
+}
+
+/// The position new players start at, standing on the highest solid block
+/// near the spawn point of the world, up to the spawn radius away from it.
+fn spawn_position(game_state: &mut GameState) -> PlayerPosition {
+    let level_data = game_state.world().level_data();
+    let (spawn_x, spawn_z) = (level_data.spawn_x, level_data.spawn_z);
+
+    let radius = game_state.config().spawn_radius;
+    let mut rng = rand::thread_rng();
+    let x = spawn_x + rng.gen_range(-radius..=radius);
+    let z = spawn_z + rng.gen_range(-radius..=radius);
+
+    // Without anywhere safe nearby, the top of the spawn point is the best
+    // there is
+    let world = game_state.world_mut();
+    let (x, y, z) = world.find_safe_position(x, z, SAFE_SPAWN_SEARCH_RADIUS)
+        .unwrap_or_else(|| (spawn_x, world.top(spawn_x, spawn_z), spawn_z));
+
+    PlayerPosition {
+        position_x: x as f64 + 0.5,
+        position_y: y as f64,
+        stance: y as f64 + PLAYER_EYE_HEIGHT,
+        position_z: z as f64 + 0.5,
+    }
+}
+
+fn handle_event(game_state: &mut GameState, event: ServerEvent) {
+    match event {
+        ServerEvent::Join { mut player, mut connection } => {
+            let username = player.username().clone();
+            let eid = player.eid();
+
+            let white_list = game_state.config().white_list;
+            if let Err(reason) = game_state.access().check_login(&username, player.address(), white_list) {
+                info!("Disconnecting {username} [{}]: {reason}", player.address());
+                connection.kick(reason);
+                return
+            }
+
+            // Logging in again replaces the old session instead of taking
+            // another slot
+            let old_session = game_state.online_player(&username).map(|p| (p.eid(), p.username().clone()));
+            let others_online = game_state.online_players().len() - old_session.is_some() as usize;
+            if others_online >= game_state.config().max_players {
+                info!("Disconnecting {username} [{}]: The server is full!", player.address());
+                connection.kick("The server is full!");
+                return
+            }
+            if let Some((old_eid, old_username)) = old_session {
+                if let Some(c) = game_state.connections_mut().get_mut(&old_eid) {
+                    c.kick("You logged in from another location");
+                }
+                info!("{old_username} logged in from another location");
+                remove_player(game_state, old_eid);
+            }
+
+            // Players who played before carry on where they left off
+            match game_state.world().load_player(&username) {
+                Ok(Some(data)) => player.load_data(data),
+                Ok(None) => player.set_position(spawn_position(game_state)),
+                Err(e) => {
+                    error!("Loading the data of {username} failed: {e}");
+                    connection.kick("Failed to load your player data");
+                    return
+                }
+            }
+            player.mark_position_sent();
+            player.mark_packet_received(game_state.tick_count());
+
+            // Show the new player to everyone else, and everyone else to them
+            game_state.broadcast(player.spawn_packet());
+            let others: Vec<_> = game_state.player_list()
+                .values()
+                .filter(|p| game_state.is_connected(p.eid()))
+                .map(|p| p.spawn_packet())
+                .collect();
+
+            game_state.connections_mut().insert(eid, connection);
+            let seed = game_state.world().seed();
+            game_state.send_to(eid, Packet1Login::new(eid, seed, player.dimension() as i8));
+            send_world(game_state, &player);
+            game_state.player_list_mut().insert(username.clone(), *player);
+            for spawn in others {
+                game_state.send_to(eid, spawn);
+            }
+
+            game_state.broadcast_chat(&format!("§e{username} joined the game."));
+        }
+        ServerEvent::Packet { eid, packet } => {
+            let tick = game_state.tick_count();
+            if let Some(player) = game_state.player_by_eid_mut(eid) {
+                player.mark_packet_received(tick);
+            }
+            handle_command(game_state, eid, packet);
+        }
+        ServerEvent::Console(line) => commands::dispatch(game_state, CommandSender::Console, &line),
+        ServerEvent::Shutdown => unreachable!("shutting down is handled by the tick loop"),
+        ServerEvent::Disconnected { eid, reason } => {
+            if let Some(player) = game_state.player_by_eid(eid) {
+                info!("{} lost connection: {reason}", player.username());
+            }
+            remove_player(game_state, eid);
+        }
+    }
+}
+
+/// Drop a player and their connection, save them, and tell everyone else
+/// they left. Does nothing if they were already removed, like a player who
+/// was replaced by a newer login.
+fn remove_player(game_state: &mut GameState, eid: i32) {
+    game_state.connections_mut().remove(&eid);
+    let Some(player) = game_state.remove_player(eid) else {
+        return
+    };
+    game_state.save_player(&player);
+
+    game_state.broadcast(Packet29DestroyEntity { entity_id: eid });
+    game_state.broadcast_chat(&format!("§e{} left the game.", player.username()));
+}
+
+fn handle_command(game_state: &mut GameState, eid: i32, packet: ServerboundPacket) {
+    let Some(player_state) = game_state.player_by_eid_mut(eid) else {
+        return
+    };
+
+    match packet {
+        ServerboundPacket::ChatMessage(chat) => {
+            let username = player_state.username().clone();
+            handle_chat(game_state, eid, &username, &chat.message.to_string());
+        }
+        ServerboundPacket::Player(_) => (),
+        ServerboundPacket::PlayerLook(look) => {
+            player_state.set_look(look.look);
+        }
+        ServerboundPacket::PlayerPosition(pos) => {
+            player_state.set_position(pos.position);
+        }
+        ServerboundPacket::PlayerPositionAndLook(poslook) => {
+            player_state.set_look(poslook.position_look.look);
+            player_state.set_position(poslook.position_look.position);
+        }
+        ServerboundPacket::HoldingChange(switch) => handle_holding_change(game_state, eid, switch.item),
+        ServerboundPacket::PlayerInventory(inventory) => handle_inventory(game_state, eid, inventory),
+        ServerboundPacket::PlayerDigging(dig) => handle_digging(game_state, eid, dig),
+        ServerboundPacket::PlayerBlockPlacement(place) => handle_place(game_state, eid, place),
+        ServerboundPacket::Animation(_) => (),
+        ServerboundPacket::KeepAlive(_) => (),
+        p => info!("Ignoring {:?} packet", p.id()),
+    }
+}
+
+fn handle_chat(game_state: &mut GameState, eid: i32, username: &str, message: &str) {
+    let message = message.trim();
+    if message.chars().count() > MAX_CHAT_LENGTH {
+        if let Some(c) = game_state.connections_mut().get_mut(&eid) {
+            c.kick("Chat message too long");
+        }
+        return
+    }
+
+    if message.is_empty() {
+        return
+    }
+
+    if let Some(command) = message.strip_prefix('/') {
+        info!("{username} issued server command: {message}");
+        commands::dispatch(game_state, CommandSender::Player(eid), command);
+        return
+    }
+
+    game_state.broadcast_chat(&format!("<{username}> {message}"));
+}
+
+/// Take the contents of an inventory section from the client, or send it
+/// back what the server has if they aren't valid.
+fn handle_inventory(game_state: &mut GameState, eid: i32, inventory: Packet5PlayerInventory) {
+    let Some(section) = InventorySection::from_i32(inventory.inventory_type) else {
+        debug!("Ignoring unknown inventory type {}", inventory.inventory_type);
+        return
+    };
+    let Some(player) = game_state.player_by_eid_mut(eid) else {
+        return
+    };
+
+    if let Err(e) = player.inventory_mut().set_section(section, &inventory.items) {
+        warn!("{} sent an invalid inventory: {e}", player.username());
+        let packet = player.inventory().packet(section);
+        game_state.send_to(eid, packet);
+        return
+    }
+
+    // The held item may have been moved out of the hotbar
+    let holding = *player.holding();
+    if holding != BlockItem::Unknown && !player.inventory().hotbar_contains(holding) {
+        handle_holding_change(game_state, eid, BlockItem::Unknown);
+    }
+}
+
+/// Change the item in a player's hand, if it is in their hotbar, and show it
+/// to everyone else.
+fn handle_holding_change(game_state: &mut GameState, eid: i32, item: BlockItem) {
+    // An empty hand is sent as air
+    let item = match item {
+        BlockItem::Block(Block::Air) => BlockItem::Unknown,
+        i => i,
+    };
+
+    let Some(player) = game_state.player_by_eid_mut(eid) else {
+        return
+    };
+    if item != BlockItem::Unknown && !player.inventory().hotbar_contains(item) {
+        debug!("{} tried to hold {item:?}, which isn't in their hotbar", player.username());
+        return
+    }
+    if *player.holding() == item {
+        return
+    }
+
+    player.set_holding(item);
+    game_state.broadcast_except(eid, Packet16BlockItemSwitch {
+        entity_id: eid,
+        item: BlockItem::from_id(item.id().max(0)),
+    });
+}
+
+/// Whether a block is close enough for a player to reach.
+fn in_reach(player: &PlayerState, x: i32, y: i32, z: i32) -> bool {
+    let position = player.position();
+    let dx = position.position_x - (x as f64 + 0.5);
+    let dy = position.stance - (y as f64 + 0.5);
+    let dz = position.position_z - (z as f64 + 0.5);
+
+    dx * dx + dy * dy + dz * dz <= MAX_REACH * MAX_REACH
+}
+
+fn handle_digging(game_state: &mut GameState, eid: i32, dig: Packet14BlockDig) {
+    if dig.status != DiggingStatus::BlockBroken {
+        return
+    }
+
+    let Some(player) = game_state.player_by_eid_mut(eid) else {
+        return
+    };
+    let allowed = player.has_chunk_loaded(dig.x >> 4, dig.z >> 4)
+        && in_reach(player, dig.x, dig.y as i32, dig.z);
+
+    let breakable = game_state.world_mut()
+        .block(dig.x, dig.y, dig.z)
+        .and_then(|(id, _)| Block::from_u8(id))
+        .is_some_and(|b| b.is_breakable());
+
+    if !allowed || !breakable {
+        game_state.send_block(eid, dig.x, dig.y, dig.z);
+        return
+    }
+
+    game_state.set_block(dig.x, dig.y, dig.z, Block::Air as u8, 0);
+}
+
+fn handle_place(game_state: &mut GameState, eid: i32, place: Packet15Place) {
+    // Using an item without aiming at a block
+    let Some(face) = Direction::from_u8(place.direction) else {
+        return
+    };
+
+    let (dx, dy, dz) = face.offset();
+    let x = place.x_position + dx;
+    let y = place.y_position as i32 + dy;
+    let z = place.z_position + dz;
+    let Ok(y) = u8::try_from(y) else {
+        return
+    };
+
+    // Items like signs, doors and seeds aren't placed yet, but the client
+    // has already put down what it thinks they make
+    let BlockItem::Block(block) = place.id else {
+        game_state.send_block(eid, x, y, z);
+        return
+    };
+
+    let Some(player) = game_state.player_by_eid_mut(eid) else {
+        return
+    };
+    let allowed = *player.holding() == place.id
+        && player.has_chunk_loaded(x >> 4, z >> 4)
+        && in_reach(player, x, y as i32, z);
+
+    let replaceable = game_state.world_mut()
+        .block(x, y, z)
+        .and_then(|(id, _)| Block::from_u8(id))
+        .is_some_and(|b| b.is_replaceable());
+
+    if !allowed || !replaceable {
+        game_state.send_block(eid, x, y, z);
+        return
+    }
+
+    game_state.set_block(x, y, z, block as u8, 0);
+}
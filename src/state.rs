@@ -1,20 +1,272 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    net::IpAddr,
+    time::Duration,
+};
+
+use log::{debug, error, info};
 
 use crate::{
-    blocks_items::BlockItem, chunk::MapChunk, position::{PlayerLook, PlayerPosition, PlayerPositionLook}
+    access::AccessLists,
+    blocks_items::{BlockItem, BlockItemID},
+    chunk::{MapChunk, PreChunk},
+    commands::CommandRegistry,
+    config::ServerConfig,
+    connection::Connection,
+    inventory::Inventory,
+    mcstring::{strip_color_codes, MCString},
+    packets::{
+        packet20_named_entity_spawn::Packet20NamedEntitySpawn,
+        packet31_rel_entity_move::Packet31RelEntityMove,
+        packet32_entity_look::Packet32EntityLook,
+        packet33_rel_entity_move_look::Packet33RelEntityMoveLook,
+        packet34_entity_teleport::Packet34EntityTeleport,
+        packet13_player_look_move::Packet13PlayerLookMove,
+        packet3_chat::Packet3Chat,
+        packet4_update_time::Packet4UpdateTime,
+        packet53_block_change::Packet53BlockChange,
+        packet6_spawn_position::Packet6SpawnPosition,
+        ClientboundPacket,
+    },
+    position::{AbsolutePosition, PlayerLook, PlayerPosition, PlayerPositionLook},
+    world::{PlayerData, WorldState},
 };
 
+/// The number of recent tick times kept to calculate the average.
+pub const TICK_TIME_SAMPLES: usize = 100;
+
+/// How often changed chunks are saved, in ticks.
+pub const AUTOSAVE_INTERVAL: u64 = 1200;
+
+/// The health of a player who isn't hurt, which is ten hearts.
+pub const MAX_HEALTH: i16 = 20;
+
+/// How often every player is sent the time, in ticks.
+const TIME_UPDATE_INTERVAL: u64 = 20;
+
+/// How often chunks no player can see are dropped from memory, in ticks.
+const CHUNK_UNLOAD_INTERVAL: u64 = 20;
+
+/// The most chunks a single player is sent in one tick.
+const CHUNKS_PER_TICK: usize = 4;
+
+/// The state of the game, owned by the server thread.
 pub struct GameState {
+    config: ServerConfig,
+    access: AccessLists,
+    commands: CommandRegistry,
+    world: WorldState,
     player_list: BTreeMap<String, PlayerState>,
+    connections: BTreeMap<i32, Connection>,
+    /// Whether the time of day is stopped
+    time_frozen: bool,
+    tick_count: u64,
+    tick_times: [Duration; TICK_TIME_SAMPLES],
+    /// Set once the server has been asked to stop
+    stopping: bool,
 }
 
 impl GameState {
-    pub fn new() -> Self {
+    pub fn new(world: WorldState, config: ServerConfig, access: AccessLists) -> Self {
         Self {
-            player_list: BTreeMap::new()
+            config,
+            access,
+            commands: CommandRegistry::default(),
+            world,
+            player_list: BTreeMap::new(),
+            connections: BTreeMap::new(),
+            time_frozen: false,
+            tick_count: 0,
+            tick_times: [Duration::ZERO; TICK_TIME_SAMPLES],
+            stopping: false,
+        }
+    }
+
+    /// Advance the game by one tick.
+    pub fn tick(&mut self) {
+        self.tick_count += 1;
+        if !self.time_frozen {
+            self.world.level_data_mut().time += 1;
+        }
+        if self.tick_count.is_multiple_of(TIME_UPDATE_INTERVAL) {
+            self.broadcast(Packet4UpdateTime { time: self.world_time() });
+        }
+
+        self.update_tracked_players();
+        self.update_loaded_chunks();
+
+        if self.tick_count.is_multiple_of(CHUNK_UNLOAD_INTERVAL) {
+            self.unload_chunks();
+        }
+
+        if self.tick_count.is_multiple_of(AUTOSAVE_INTERVAL) {
+            self.save();
+        }
+    }
+
+    /// Drop the chunks which are out of sight of every player. Loading a
+    /// chunk loads the chunks around it as well, to populate it, so those
+    /// are kept too.
+    fn unload_chunks(&mut self) {
+        let radius = self.config.view_distance + 1;
+        let centres: Vec<_> = self.player_list.values()
+            .filter(|p| self.connections.contains_key(&p.eid))
+            .map(|p| p.chunk_position())
+            .collect();
+
+        let keep = |x: i32, z: i32| centres.iter()
+            .any(|(cx, cz)| (x - cx).abs() <= radius && (z - cz).abs() <= radius);
+        match self.world.unload_chunks(keep) {
+            Ok(0) => (),
+            Ok(count) => debug!("Unloaded {count} chunks"),
+            Err(e) => error!("Saving chunks before unloading them failed: {e}"),
+        }
+    }
+
+    /// Save the world and every player to disk.
+    pub fn save(&mut self) {
+        for player in self.player_list.values() {
+            self.save_player(player);
+        }
+
+        match self.world.save_all() {
+            Ok(0) => (),
+            Ok(count) => info!("Saved {count} chunks"),
+            Err(e) => error!("Saving the world failed: {e}"),
+        }
+    }
+
+    /// Save the data of a player to their file in the world.
+    pub fn save_player(&self, player: &PlayerState) {
+        if let Err(e) = self.world.save_player(player.username(), &player.data()) {
+            error!("Saving {} failed: {e}", player.username());
+        }
+    }
+
+    /// Send the movement of every player since the last tick to everyone else.
+    fn update_tracked_players(&mut self) {
+        let mut updates = Vec::new();
+        for player in self.player_list.values_mut() {
+            if !self.connections.contains_key(&player.eid) {
+                continue
+            }
+
+            let current = AbsolutePosition::from(&player.position_look);
+            let last = player.sent_position;
+            if current == last {
+                continue
+            }
+            player.sent_position = current;
+
+            let dx = current.x - last.x;
+            let dy = current.y - last.y;
+            let dz = current.z - last.z;
+            let moved = dx != 0 || dy != 0 || dz != 0;
+            let looked = current.yaw != last.yaw || current.pitch != last.pitch;
+
+            let eid = player.eid;
+            let packet: ClientboundPacket = match (i8::try_from(dx), i8::try_from(dy), i8::try_from(dz)) {
+                (Ok(x), Ok(y), Ok(z)) if moved && looked => Packet33RelEntityMoveLook {
+                    entity_id: eid, x, y, z, yaw: current.yaw, pitch: current.pitch,
+                }.into(),
+                (Ok(x), Ok(y), Ok(z)) if moved => Packet31RelEntityMove {
+                    entity_id: eid, x, y, z,
+                }.into(),
+                (Ok(_), Ok(_), Ok(_)) => Packet32EntityLook {
+                    entity_id: eid, yaw: current.yaw, pitch: current.pitch,
+                }.into(),
+                // Moved too far for a relative move
+                _ => Packet34EntityTeleport {
+                    entity_id: eid,
+                    x: current.x,
+                    y: current.y,
+                    z: current.z,
+                    yaw: current.yaw,
+                    pitch: current.pitch,
+                }.into(),
+            };
+            updates.push((eid, packet));
+        }
+
+        for (eid, packet) in updates {
+            self.broadcast_except(eid, packet);
+        }
+    }
+
+    /// Load the chunks around every player as they move, nearest first, and
+    /// unload the chunks they moved away from.
+    fn update_loaded_chunks(&mut self) {
+        let radius = self.config.view_distance;
+        for player in self.player_list.values_mut() {
+            let Some(connection) = self.connections.get_mut(&player.eid) else {
+                continue
+            };
+            let (centre_x, centre_z) = player.chunk_position();
+            let in_range = |x: i32, z: i32| (x - centre_x).abs() <= radius && (z - centre_z).abs() <= radius;
+
+            let unload: Vec<_> = player.loaded_chunks.iter()
+                .filter(|(x, z)| !in_range(*x, *z))
+                .copied()
+                .collect();
+            for (x, z) in unload {
+                player.loaded_chunks.remove(&(x, z));
+                connection.send(PreChunk::new_unload(x, z));
+            }
+
+            let mut load: Vec<_> = (centre_x - radius..=centre_x + radius)
+                .flat_map(|x| (centre_z - radius..=centre_z + radius).map(move |z| (x, z)))
+                .filter(|c| !player.loaded_chunks.contains(c))
+                .collect();
+            load.sort_by_key(|(x, z)| (x - centre_x).pow(2) + (z - centre_z).pow(2));
+            for (x, z) in load.into_iter().take(CHUNKS_PER_TICK) {
+                let chunk = MapChunk::new(x * 16, z * 16, self.world.chunk(x, z).blocks());
+                connection.send(PreChunk::new_load(x, z));
+                connection.send(chunk);
+                player.loaded_chunks.insert((x, z));
+            }
+        }
+    }
+
+    /// Hand the packets queued during this tick to every connection.
+    pub fn flush_connections(&mut self) {
+        for connection in self.connections.values_mut() {
+            connection.flush();
         }
     }
 
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut ServerConfig {
+        &mut self.config
+    }
+
+    pub fn access(&self) -> &AccessLists {
+        &self.access
+    }
+
+    pub fn access_mut(&mut self) -> &mut AccessLists {
+        &mut self.access
+    }
+
+    pub fn commands(&self) -> &CommandRegistry {
+        &self.commands
+    }
+
+    pub fn commands_mut(&mut self) -> &mut CommandRegistry {
+        &mut self.commands
+    }
+
+    pub fn world(&self) -> &WorldState {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut WorldState {
+        &mut self.world
+    }
+
     pub fn player_list(&self) -> &BTreeMap<String, PlayerState> {
         &self.player_list
     }
@@ -22,48 +274,249 @@ impl GameState {
     pub fn player_list_mut(&mut self) -> &mut BTreeMap<String, PlayerState> {
         &mut self.player_list
     }
+
+    pub fn player_by_eid(&self, eid: i32) -> Option<&PlayerState> {
+        self.player_list.values().find(|p| p.eid() == eid)
+    }
+
+    pub fn player_by_eid_mut(&mut self, eid: i32) -> Option<&mut PlayerState> {
+        self.player_list.values_mut().find(|p| p.eid() == eid)
+    }
+
+    /// Take a player out of the player list.
+    pub fn remove_player(&mut self, eid: i32) -> Option<PlayerState> {
+        let username = self.player_by_eid(eid)?.username().clone();
+        self.player_list.remove(&username)
+    }
+
+    /// The connected player with a name, ignoring case.
+    pub fn online_player(&self, name: &str) -> Option<&PlayerState> {
+        self.player_list.values()
+            .filter(|p| self.connections.contains_key(&p.eid))
+            .find(|p| p.username.eq_ignore_ascii_case(name))
+    }
+
+    /// The names of every connected player.
+    pub fn online_players(&self) -> Vec<&str> {
+        self.player_list.values()
+            .filter(|p| self.connections.contains_key(&p.eid))
+            .map(|p| p.username.as_str())
+            .collect()
+    }
+
+    /// Whether the player with the given entity ID is still connected.
+    pub fn is_connected(&self, eid: i32) -> bool {
+        self.connections.contains_key(&eid)
+    }
+
+    /// The outgoing connection of every player, by entity ID.
+    pub fn connections_mut(&mut self) -> &mut BTreeMap<i32, Connection> {
+        &mut self.connections
+    }
+
+    /// Queue a packet for the player with the given entity ID.
+    pub fn send_to<P: Into<ClientboundPacket>>(&mut self, eid: i32, packet: P) {
+        if let Some(c) = self.connections.get_mut(&eid) {
+            c.send(packet);
+        }
+    }
+
+    /// Queue a packet for every connected player.
+    pub fn broadcast<P: Into<ClientboundPacket>>(&mut self, packet: P) {
+        let packet = packet.into();
+        for connection in self.connections.values_mut() {
+            connection.send(packet.clone());
+        }
+    }
+
+    /// Queue a packet for every connected player except one.
+    pub fn broadcast_except<P: Into<ClientboundPacket>>(&mut self, eid: i32, packet: P) {
+        let packet = packet.into();
+        for (_, connection) in self.connections.iter_mut().filter(|(e, _)| **e != eid) {
+            connection.send(packet.clone());
+        }
+    }
+
+    /// Change a block in the world, sending the change to every player who
+    /// has its chunk loaded.
+    pub fn set_block(&mut self, x: i32, y: u8, z: i32, block: u8, metadata: u8) {
+        if !self.world.set_block(x, y, z, block, metadata) {
+            return
+        }
+
+        let packet = ClientboundPacket::from(Packet53BlockChange {
+            x,
+            y,
+            z,
+            block_type: block,
+            metadata,
+        });
+        let watchers: Vec<_> = self.player_list.values()
+            .filter(|p| p.has_chunk_loaded(x >> 4, z >> 4))
+            .map(|p| p.eid)
+            .collect();
+        for eid in watchers {
+            self.send_to(eid, packet.clone());
+        }
+    }
+
+    /// Send a block as it is in the world to one player, undoing a change
+    /// their client made on its own.
+    pub fn send_block(&mut self, eid: i32, x: i32, y: u8, z: i32) {
+        if let Some((block_type, metadata)) = self.world.block(x, y, z) {
+            self.send_to(eid, Packet53BlockChange {
+                x,
+                y,
+                z,
+                block_type,
+                metadata,
+            });
+        }
+    }
+
+    /// Move a player, telling their client where they now are.
+    pub fn teleport(&mut self, eid: i32, position: PlayerPosition) {
+        let Some(player) = self.player_by_eid_mut(eid) else {
+            return
+        };
+        player.set_position(position);
+
+        let packet = Packet13PlayerLookMove::new_clientbound(player.position_look);
+        self.send_to(eid, packet);
+    }
+
+    /// Send a chat message to a single player.
+    pub fn send_chat(&mut self, eid: i32, message: &str) {
+        if let Ok(message) = MCString::try_from(message) {
+            self.send_to(eid, Packet3Chat { message });
+        }
+    }
+
+    /// Send a chat message to every connected player.
+    pub fn broadcast_chat(&mut self, message: &str) {
+        info!("{}", strip_color_codes(message));
+        let Ok(message) = MCString::try_from(message) else {
+            return
+        };
+        self.broadcast(Packet3Chat { message });
+    }
+
+    /// The number of ticks the world has run for, which is saved with it.
+    pub fn world_time(&self) -> i64 {
+        self.world.level_data().time
+    }
+
+    /// The packet which tells clients where the spawn point of the world is,
+    /// which their compass points to.
+    pub fn spawn_point_packet(&self) -> Packet6SpawnPosition {
+        let level_data = self.world.level_data();
+        Packet6SpawnPosition {
+            x: level_data.spawn_x,
+            y: level_data.spawn_y,
+            z: level_data.spawn_z,
+        }
+    }
+
+    /// Move the spawn point of the world, and tell every player about it.
+    pub fn set_spawn_point(&mut self, x: i32, y: i32, z: i32) -> Result<(), io::Error> {
+        let level_data = self.world.level_data_mut();
+        level_data.spawn_x = x;
+        level_data.spawn_y = y;
+        level_data.spawn_z = z;
+        self.world.save_level_data()?;
+
+        self.broadcast(self.spawn_point_packet());
+
+        Ok(())
+    }
+
+    /// Change the time of day, and tell every player about it.
+    pub fn set_world_time(&mut self, time: i64) {
+        self.world.level_data_mut().time = time;
+        self.broadcast(Packet4UpdateTime { time });
+    }
+
+    pub fn is_time_frozen(&self) -> bool {
+        self.time_frozen
+    }
+
+    /// Stop or restart the time of day.
+    pub fn set_time_frozen(&mut self, frozen: bool) {
+        self.time_frozen = frozen
+    }
+
+    /// Ask the server to save and stop at the end of the tick.
+    pub fn stop(&mut self) {
+        self.stopping = true;
+    }
+
+    pub fn is_stopping(&self) -> bool {
+        self.stopping
+    }
+
+    pub fn tick_count(&self) -> u64 {
+        self.tick_count
+    }
+
+    pub fn record_tick_time(&mut self, time: Duration) {
+        self.tick_times[self.tick_count as usize % TICK_TIME_SAMPLES] = time;
+    }
+
+    /// The average time of the last [`TICK_TIME_SAMPLES`] ticks.
+    pub fn average_tick_time(&self) -> Duration {
+        self.tick_times.iter().sum::<Duration>() / TICK_TIME_SAMPLES as u32
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct PlayerState {
     eid: i32,
     username: String,
+    /// The address the player connected from
+    address: IpAddr,
     holding: BlockItem,
+    inventory: Inventory,
+    health: i16,
+    dimension: i32,
     position_look: PlayerPositionLook,
+    /// The position last sent to other players
+    sent_position: AbsolutePosition,
+    /// The chunks the client has been sent
+    loaded_chunks: BTreeSet<(i32, i32)>,
+    /// The tick the last packet from the client was handled on
+    last_packet_tick: u64,
 }
 
 impl PlayerState {
     /// Create a new player when they join
-    pub fn new(username: String, eid: i32,) -> Self {
+    pub fn new(username: String, eid: i32, address: IpAddr) -> Self {
         Self {
             eid,
             username,
+            address,
             holding: BlockItem::Unknown,
+            inventory: Inventory::default(),
+            health: MAX_HEALTH,
+            dimension: 0,
             position_look: PlayerPositionLook::default(),
+            sent_position: AbsolutePosition::default(),
+            loaded_chunks: BTreeSet::new(),
+            last_packet_tick: 0,
         }
     }
 
-    pub fn new_invalid() -> Self {
-        Self {
-            eid: -1,
-            username: String::new(),
-            holding: BlockItem::Unknown,
-            position_look: PlayerPositionLook::default(),
-        }
-    }
-
-    pub fn is_valid(&self) -> bool {
-        if self.eid >= 0 && self.username != String::new() {
-            true
-        } else {
-            false
-        }
+    pub fn eid(&self) -> i32 {
+        self.eid
     }
 
     pub fn username(&self) -> &String {
         &self.username
     }
 
+    pub fn address(&self) -> IpAddr {
+        self.address
+    }
+
     pub fn look(&self) -> &PlayerLook {
         &self.position_look.look
     }
@@ -91,8 +544,82 @@ impl PlayerState {
     pub fn set_holding(&mut self, holding: BlockItem) {
         self.holding = holding
     }
-}
 
-pub struct WorldState {
-    chunks: Vec<MapChunk>,
+    pub fn health(&self) -> i16 {
+        self.health
+    }
+
+    pub fn dimension(&self) -> i32 {
+        self.dimension
+    }
+
+    /// The part of the player which is saved between sessions.
+    pub fn data(&self) -> PlayerData {
+        PlayerData {
+            position_look: self.position_look,
+            health: self.health,
+            dimension: self.dimension,
+            inventory: self.inventory.clone(),
+        }
+    }
+
+    /// Restore the player from a previous session.
+    pub fn load_data(&mut self, data: PlayerData) {
+        self.position_look = data.position_look;
+        self.health = data.health;
+        self.dimension = data.dimension;
+        self.inventory = data.inventory;
+    }
+
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    pub fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+
+    /// The tick the last packet from the client was handled on.
+    pub fn last_packet_tick(&self) -> u64 {
+        self.last_packet_tick
+    }
+
+    pub fn mark_packet_received(&mut self, tick: u64) {
+        self.last_packet_tick = tick
+    }
+
+    /// The packet which shows this player to other players.
+    pub fn spawn_packet(&self) -> Packet20NamedEntitySpawn {
+        let position = AbsolutePosition::from(&self.position_look);
+        Packet20NamedEntitySpawn {
+            entity_id: self.eid,
+            name: MCString::try_from(self.username.as_str()).unwrap_or_default(),
+            x: position.x,
+            y: position.y,
+            z: position.z,
+            rotation: position.yaw,
+            pitch: position.pitch,
+            current_item: self.holding.id().max(0),
+        }
+    }
+
+    /// Whether the client has been sent the chunk at the given chunk
+    /// coordinates.
+    pub fn has_chunk_loaded(&self, x: i32, z: i32) -> bool {
+        self.loaded_chunks.contains(&(x, z))
+    }
+
+    /// The coordinates of the chunk the player is in.
+    pub fn chunk_position(&self) -> (i32, i32) {
+        let position = self.position();
+        (
+            (position.position_x.floor() as i32) >> 4,
+            (position.position_z.floor() as i32) >> 4,
+        )
+    }
+
+    /// Mark the current position as seen by other players.
+    pub fn mark_position_sent(&mut self) {
+        self.sent_position = AbsolutePosition::from(&self.position_look);
+    }
 }
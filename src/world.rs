@@ -0,0 +1,645 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{error, info};
+use num_traits::FromPrimitive;
+use rand::{random, Rng};
+
+use crate::{
+    blocks_items::Block,
+    chunk::{BlockArray, CHUNK_HEIGHT_Y},
+    generator::{PopulationArea, WorldGenerator},
+    inventory::Inventory,
+    nbt::{self, NbtCompound, Tag},
+    position::{PlayerLook, PlayerPosition, PlayerPositionLook, PLAYER_EYE_HEIGHT},
+};
+
+/// How many places are tried when looking for the spawn point of a new world.
+const SPAWN_SEARCH_TRIES: usize = 1000;
+
+/// The directory in the world which players are saved to.
+const PLAYERS_DIRECTORY: &str = "players";
+
+/// The contents of the `Data` tag in `level.dat`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelData {
+    pub seed: i64,
+    pub spawn_x: i32,
+    pub spawn_y: i32,
+    pub spawn_z: i32,
+    pub time: i64,
+    pub last_played: i64,
+    pub size_on_disk: i64,
+    /// The player of a singleplayer world, which is kept so the world can be
+    /// taken back to singleplayer
+    pub player: Option<Tag>,
+}
+
+impl LevelData {
+    fn new(seed: i64) -> Self {
+        Self {
+            seed,
+            spawn_x: 0,
+            spawn_y: 64,
+            spawn_z: 0,
+            time: 0,
+            last_played: 0,
+            size_on_disk: 0,
+            player: None,
+        }
+    }
+}
+
+impl NbtCompound for LevelData {
+    fn to_nbt(&self) -> Tag {
+        let mut tag = Tag::new_compound();
+        tag.insert("RandomSeed", self.seed);
+        tag.insert("SpawnX", self.spawn_x);
+        tag.insert("SpawnY", self.spawn_y);
+        tag.insert("SpawnZ", self.spawn_z);
+        tag.insert("Time", self.time);
+        tag.insert("LastPlayed", self.last_played);
+        tag.insert("SizeOnDisk", self.size_on_disk);
+        if let Some(player) = &self.player {
+            tag.insert("Player", player.clone());
+        }
+
+        tag
+    }
+
+    fn from_nbt(tag: &Tag) -> Option<Self> {
+        Some(Self {
+            seed: tag.get("RandomSeed")?.as_long()?,
+            spawn_x: tag.get("SpawnX")?.as_int()?,
+            spawn_y: tag.get("SpawnY")?.as_int()?,
+            spawn_z: tag.get("SpawnZ")?.as_int()?,
+            time: tag.get("Time").and_then(Tag::as_long).unwrap_or(0),
+            last_played: tag.get("LastPlayed").and_then(Tag::as_long).unwrap_or(0),
+            size_on_disk: tag.get("SizeOnDisk").and_then(Tag::as_long).unwrap_or(0),
+            player: tag.get("Player").cloned(),
+        })
+    }
+}
+
+/// The saved state of a player, stored in `players/<name>.dat`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerData {
+    pub position_look: PlayerPositionLook,
+    pub health: i16,
+    pub dimension: i32,
+    pub inventory: Inventory,
+}
+
+impl NbtCompound for PlayerData {
+    fn to_nbt(&self) -> Tag {
+        let position = &self.position_look.position;
+        let look = &self.position_look.look;
+
+        // The rest of the tags are needed for the original server to read
+        // the file
+        let mut tag = Tag::new_compound();
+        tag.insert("Pos", vec![
+            Tag::Double(position.position_x),
+            Tag::Double(position.position_y),
+            Tag::Double(position.position_z),
+        ]);
+        tag.insert("Motion", vec![Tag::Double(0.0), Tag::Double(0.0), Tag::Double(0.0)]);
+        tag.insert("Rotation", vec![Tag::Float(look.yaw), Tag::Float(look.pitch)]);
+        tag.insert("FallDistance", 0.0f32);
+        tag.insert("Fire", -20i16);
+        tag.insert("Air", 300i16);
+        tag.insert("OnGround", 1i8);
+        tag.insert("Health", self.health);
+        tag.insert("HurtTime", 0i16);
+        tag.insert("DeathTime", 0i16);
+        tag.insert("AttackTime", 0i16);
+        tag.insert("Dimension", self.dimension);
+        tag.insert("Inventory", self.inventory.to_nbt());
+
+        tag
+    }
+
+    fn from_nbt(tag: &Tag) -> Option<Self> {
+        let position = tag.get("Pos")?.as_list()?;
+        let rotation = tag.get("Rotation")?.as_list()?;
+        let position_y = position.get(1)?.as_double()?;
+
+        Some(Self {
+            position_look: PlayerPositionLook {
+                position: PlayerPosition {
+                    position_x: position.first()?.as_double()?,
+                    position_y,
+                    stance: position_y + PLAYER_EYE_HEIGHT,
+                    position_z: position.get(2)?.as_double()?,
+                },
+                look: PlayerLook {
+                    yaw: rotation.first()?.as_float()?,
+                    pitch: rotation.get(1)?.as_float()?,
+                },
+            },
+            health: tag.get("Health").and_then(Tag::as_short).unwrap_or(20),
+            dimension: tag.get("Dimension").and_then(Tag::as_int).unwrap_or(0),
+            inventory: tag.get("Inventory").map(Inventory::from_nbt).unwrap_or_default(),
+        })
+    }
+}
+
+/// A chunk of the world, with the rest of its saved data kept as-is.
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    blocks: BlockArray,
+    /// Everything in the chunk's `Level` tag other than the block data
+    level: BTreeMap<String, Tag>,
+    /// Whether ores, trees and the like have been added
+    populated: bool,
+    dirty: bool,
+}
+
+impl Chunk {
+    fn new(blocks: BlockArray) -> Self {
+        Self {
+            blocks,
+            level: BTreeMap::new(),
+            populated: false,
+            dirty: true,
+        }
+    }
+
+    pub fn blocks(&self) -> &BlockArray {
+        &self.blocks
+    }
+
+    /// The blocks of the chunk, for changing. This marks the chunk as needing
+    /// to be saved.
+    pub fn blocks_mut(&mut self) -> &mut BlockArray {
+        self.dirty = true;
+        &mut self.blocks
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn read(path: &Path) -> Result<Self, io::Error> {
+        let (_, root) = nbt::read_gzip(BufReader::new(File::open(path)?))?;
+        let Tag::Compound(mut root) = root else {
+            return Err(invalid_data("chunk root is not a compound"))
+        };
+        let Some(Tag::Compound(mut level)) = root.remove("Level") else {
+            return Err(invalid_data("chunk has no level tag"))
+        };
+
+        let mut take_bytes = |name| match level.remove(name) {
+            Some(Tag::ByteArray(bytes)) => Ok(bytes),
+            _ => Err(invalid_data(&format!("chunk is missing {name}"))),
+        };
+        let blocks = take_bytes("Blocks")?;
+        let metadata = take_bytes("Data")?;
+        let sky_light = take_bytes("SkyLight")?;
+        let block_light = take_bytes("BlockLight")?;
+
+        let blocks = BlockArray::from_parts(blocks, metadata, block_light, sky_light)
+            .ok_or(invalid_data("chunk data has the wrong size"))?;
+        let populated = level.remove("TerrainPopulated").and_then(|t| t.as_byte()) == Some(1);
+
+        Ok(Self {
+            blocks,
+            level,
+            populated,
+            dirty: false,
+        })
+    }
+
+    fn write(&self, path: &Path, x: i32, z: i32, world_time: i64) -> Result<(), io::Error> {
+        let mut level = self.level.clone();
+        level.insert("xPos".to_string(), x.into());
+        level.insert("zPos".to_string(), z.into());
+        level.insert("LastUpdate".to_string(), world_time.into());
+        level.insert("Blocks".to_string(), self.blocks.raw_blocks().to_vec().into());
+        level.insert("Data".to_string(), self.blocks.raw_metadata().to_vec().into());
+        level.insert("SkyLight".to_string(), self.blocks.raw_sky_light().to_vec().into());
+        level.insert("BlockLight".to_string(), self.blocks.raw_block_light().to_vec().into());
+        level.insert("HeightMap".to_string(), self.blocks.height_map().into());
+        level.insert("TerrainPopulated".to_string(), (self.populated as i8).into());
+        level.entry("Entities".to_string()).or_insert(Tag::List(Vec::new()));
+        level.entry("TileEntities".to_string()).or_insert(Tag::List(Vec::new()));
+
+        let mut root = Tag::new_compound();
+        root.insert("Level", level);
+        nbt::write_gzip(BufWriter::new(File::create(path)?), "", &root)
+    }
+}
+
+/// The world, stored in the Alpha format of one file per chunk.
+///
+/// Chunks are kept in `<world>/<x>/<z>/c.<x>.<z>.dat`, where the directories
+/// are the chunk coordinates modulo 64, and everything is written in base 36.
+pub struct WorldState {
+    directory: PathBuf,
+    level_data: LevelData,
+    chunks: BTreeMap<(i32, i32), Chunk>,
+    generator: Box<dyn WorldGenerator>,
+}
+
+impl WorldState {
+    /// Open the world in a directory, creating it if it doesn't exist.
+    ///
+    /// New chunks are created by the generator made from the seed of the
+    /// world.
+    pub fn open<P, F>(directory: P, generator: F) -> Result<Self, io::Error>
+    where
+        P: Into<PathBuf>,
+        F: FnOnce(i64) -> Box<dyn WorldGenerator>,
+    {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+
+        let level_path = directory.join("level.dat");
+        let is_new = !level_path.exists();
+        let level_data = if !is_new {
+            let (_, root) = nbt::read_gzip(BufReader::new(File::open(&level_path)?))?;
+            root.get("Data")
+                .and_then(LevelData::from_nbt)
+                .ok_or(invalid_data("level.dat has no valid data tag"))?
+        } else {
+            info!("Creating a new world in {}", directory.display());
+            LevelData::new(random())
+        };
+
+        let mut world = Self {
+            directory,
+            generator: generator(level_data.seed),
+            level_data,
+            chunks: BTreeMap::new(),
+        };
+        if is_new {
+            world.choose_spawn();
+        }
+        world.save_level_data()?;
+
+        Ok(world)
+    }
+
+    pub fn seed(&self) -> i64 {
+        self.level_data.seed
+    }
+
+    pub fn level_data(&self) -> &LevelData {
+        &self.level_data
+    }
+
+    pub fn level_data_mut(&mut self) -> &mut LevelData {
+        &mut self.level_data
+    }
+
+    /// Get a chunk by its chunk coordinates, loading or generating it if it
+    /// isn't in memory.
+    ///
+    /// The chunks around it are loaded as well, so that everything which
+    /// spreads into the chunk from its neighbours has been populated.
+    pub fn chunk(&mut self, x: i32, z: i32) -> &mut Chunk {
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                self.load_chunk(x + dx, z + dz);
+            }
+        }
+        for (px, pz) in [(x - 1, z - 1), (x, z - 1), (x - 1, z), (x, z)] {
+            self.populate(px, pz);
+        }
+
+        self.chunks.get_mut(&(x, z)).unwrap()
+    }
+
+    /// Make sure a chunk is in memory, reading or generating its terrain if
+    /// it isn't.
+    fn load_chunk(&mut self, x: i32, z: i32) {
+        if self.chunks.contains_key(&(x, z)) {
+            return
+        }
+
+        let path = self.chunk_path(x, z);
+        if path.exists() {
+            match Chunk::read(&path) {
+                Ok(chunk) => {
+                    self.chunks.insert((x, z), chunk);
+                    return
+                }
+                Err(e) => {
+                    // Keep the broken file, rather than saving over it with
+                    // the new chunk
+                    let corrupt_path = path.with_extension("dat.corrupt");
+                    error!("Failed to load chunk {x}, {z}, moving it to {} and generating it again: {e}", corrupt_path.display());
+                    if let Err(e) = fs::rename(&path, &corrupt_path) {
+                        error!("Failed to move chunk {x}, {z} aside: {e}");
+                    }
+                }
+            }
+        }
+
+        self.chunks.insert((x, z), Chunk::new(self.generator.generate(x, z)));
+    }
+
+    /// Populate a chunk if it hasn't been yet and the chunks it spreads
+    /// into are in memory.
+    fn populate(&mut self, x: i32, z: i32) {
+        let area = [(x, z), (x + 1, z), (x, z + 1), (x + 1, z + 1)];
+        let ready = area.iter().all(|position| self.chunks.contains_key(position));
+        if !ready || self.chunks[&(x, z)].populated {
+            return
+        }
+
+        let mut chunks = area.map(|position| self.chunks.remove(&position).unwrap());
+        let mut population = PopulationArea::new(x, z, chunks.each_mut().map(|c| &mut c.blocks));
+        self.generator.populate(&mut population);
+
+        chunks[0].populated = true;
+        for (position, mut chunk) in area.into_iter().zip(chunks) {
+            chunk.blocks.update_sky_light();
+            chunk.dirty = true;
+            self.chunks.insert(position, chunk);
+        }
+    }
+
+    /// The ID and metadata of a block, or [`None`] if it is outside the
+    /// world.
+    pub fn block(&mut self, x: i32, y: u8, z: i32) -> Option<(u8, u8)> {
+        if y as usize >= CHUNK_HEIGHT_Y {
+            return None
+        }
+
+        let (local_x, y, local_z) = ((x & 15) as usize, y as usize, (z & 15) as usize);
+        let blocks = self.chunk(x >> 4, z >> 4).blocks();
+        Some((blocks.block(local_x, y, local_z), blocks.metadata(local_x, y, local_z)))
+    }
+
+    /// Change a block, returning `false` if it is outside the world.
+    pub fn set_block(&mut self, x: i32, y: u8, z: i32, block: u8, metadata: u8) -> bool {
+        if y as usize >= CHUNK_HEIGHT_Y {
+            return false
+        }
+
+        let (local_x, y, local_z) = ((x & 15) as usize, y as usize, (z & 15) as usize);
+        let blocks = self.chunk(x >> 4, z >> 4).blocks_mut();
+        blocks.set_block(local_x, y, local_z, block);
+        blocks.set_metadata(local_x, y, local_z, metadata);
+
+        true
+    }
+
+    /// The lowest Y position above every block which blocks light in a
+    /// column of the world.
+    pub fn height(&mut self, x: i32, z: i32) -> i32 {
+        self.chunk(x >> 4, z >> 4).blocks().height((x & 15) as usize, (z & 15) as usize) as i32
+    }
+
+    /// The lowest Y position above every block which isn't air in a column
+    /// of the world.
+    pub fn top(&mut self, x: i32, z: i32) -> i32 {
+        self.chunk(x >> 4, z >> 4).blocks().top((x & 15) as usize, (z & 15) as usize) as i32
+    }
+
+    /// The Y position a player can stand at in a column, on top of the
+    /// highest solid block. There is nowhere safe to stand if that is
+    /// covered by a liquid, or is something harmful like fire or a cactus.
+    pub fn safe_height(&mut self, x: i32, z: i32) -> Option<i32> {
+        for y in (0..self.top(x, z)).rev() {
+            let block = self.block(x, y as u8, z).and_then(|(id, _)| Block::from_u8(id))?;
+            if block.is_liquid() || matches!(block, Block::Fire | Block::Cactus) {
+                return None
+            }
+            if block.is_solid() {
+                return Some(y + 1)
+            }
+        }
+
+        None
+    }
+
+    /// The closest column to a position which has somewhere safe to stand,
+    /// looking at most `radius` blocks away, as an `(x, y, z)` position.
+    pub fn find_safe_position(&mut self, x: i32, z: i32, radius: i32) -> Option<(i32, i32, i32)> {
+        for distance in 0..=radius {
+            for dx in -distance..=distance {
+                for dz in -distance..=distance {
+                    // Only the ring at this distance, the inside was checked
+                    // already
+                    if dx.abs() != distance && dz.abs() != distance {
+                        continue
+                    }
+
+                    if let Some(y) = self.safe_height(x + dx, z + dz) {
+                        return Some((x + dx, y, z + dz))
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Pick the spawn point of a new world, wandering away from the origin
+    /// until there is land to stand on like the original does.
+    fn choose_spawn(&mut self) {
+        let mut rng = rand::thread_rng();
+        let (mut x, mut z) = (0, 0);
+        for _ in 0..SPAWN_SEARCH_TRIES {
+            if let Some(y) = self.safe_height(x, z) {
+                self.level_data.spawn_x = x;
+                self.level_data.spawn_y = y;
+                self.level_data.spawn_z = z;
+                return
+            }
+
+            x += rng.gen_range(0..64) - rng.gen_range(0..64);
+            z += rng.gen_range(0..64) - rng.gen_range(0..64);
+        }
+
+        self.level_data.spawn_y = self.top(0, 0);
+    }
+
+    /// The number of chunks in memory.
+    pub fn loaded_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Write `level.dat`, keeping the previous one as `level.dat_old`.
+    pub fn save_level_data(&self) -> Result<(), io::Error> {
+        let mut data = self.level_data.clone();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        data.last_played = now.as_millis() as i64;
+
+        let mut root = Tag::new_compound();
+        root.insert("Data", data.to_nbt());
+        let new_path = self.directory.join("level.dat_new");
+        let path = self.directory.join("level.dat");
+        let old_path = self.directory.join("level.dat_old");
+
+        nbt::write_gzip(BufWriter::new(File::create(&new_path)?), "", &root)?;
+        if path.exists() {
+            fs::rename(&path, &old_path)?;
+        }
+        fs::rename(&new_path, &path)
+    }
+
+    /// Read the saved data of a player, if they have played before.
+    pub fn load_player(&self, username: &str) -> Result<Option<PlayerData>, io::Error> {
+        let path = self.player_path(username)?;
+        let file = match File::open(&path) {
+            Ok(f) => f,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let (_, root) = nbt::read_gzip(BufReader::new(file))?;
+        PlayerData::from_nbt(&root)
+            .map(Some)
+            .ok_or(invalid_data("player data is missing its position"))
+    }
+
+    /// Write the data of a player, replacing what was saved before.
+    pub fn save_player(&self, username: &str, data: &PlayerData) -> Result<(), io::Error> {
+        let path = self.player_path(username)?;
+        fs::create_dir_all(self.directory.join(PLAYERS_DIRECTORY))?;
+
+        let temp_path = path.with_extension("dat_tmp");
+        nbt::write_gzip(BufWriter::new(File::create(&temp_path)?), "", &data.to_nbt())?;
+        fs::rename(&temp_path, &path)
+    }
+
+    /// The file a player is saved in. Names which could lead outside the
+    /// players directory are refused.
+    ///
+    /// Names are kept in lowercase, since players are told apart ignoring
+    /// case everywhere else.
+    fn player_path(&self, username: &str) -> Result<PathBuf, io::Error> {
+        let valid = !username.is_empty()
+            && username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("\"{username}\" can't be used as a file name"),
+            ))
+        }
+
+        Ok(self.directory.join(PLAYERS_DIRECTORY).join(format!("{}.dat", username.to_lowercase())))
+    }
+
+    /// Save every chunk which changed since it was last saved, returning the
+    /// number of chunks written.
+    pub fn save_chunks(&mut self) -> Result<usize, io::Error> {
+        let world_time = self.level_data.time;
+
+        let mut saved = 0;
+        for (&(x, z), chunk) in self.chunks.iter_mut().filter(|(_, c)| c.dirty) {
+            write_chunk(&self.directory, x, z, chunk, world_time)?;
+            chunk.dirty = false;
+            saved += 1;
+        }
+
+        Ok(saved)
+    }
+
+    /// Drop every chunk for which `keep` is false from memory, saving it
+    /// first if it changed. Returns the number of chunks unloaded.
+    pub fn unload_chunks<F: Fn(i32, i32) -> bool>(&mut self, keep: F) -> Result<usize, io::Error> {
+        let world_time = self.level_data.time;
+        let unload: Vec<_> = self.chunks.keys()
+            .filter(|(x, z)| !keep(*x, *z))
+            .copied()
+            .collect();
+
+        for &(x, z) in &unload {
+            let chunk = &self.chunks[&(x, z)];
+            if chunk.dirty {
+                write_chunk(&self.directory, x, z, chunk, world_time)?;
+            }
+            self.chunks.remove(&(x, z));
+        }
+
+        Ok(unload.len())
+    }
+
+    /// Save `level.dat` and every changed chunk.
+    pub fn save_all(&mut self) -> Result<usize, io::Error> {
+        self.save_level_data()?;
+        self.save_chunks()
+    }
+
+    fn chunk_path(&self, x: i32, z: i32) -> PathBuf {
+        self.directory.join(chunk_file_path(x, z))
+    }
+}
+
+/// Write a chunk to its file in the world directory.
+fn write_chunk(directory: &Path, x: i32, z: i32, chunk: &Chunk, world_time: i64) -> Result<(), io::Error> {
+    let path = directory.join(chunk_file_path(x, z));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Write to a temporary file first so a crash can't leave a half-written
+    // chunk behind
+    let temp_path = directory.join("tmp_chunk.dat");
+    chunk.write(&temp_path, x, z, world_time)?;
+    fs::rename(&temp_path, &path)
+}
+
+/// The path of a chunk file relative to the world directory.
+fn chunk_file_path(x: i32, z: i32) -> PathBuf {
+    PathBuf::from(base36(x & 63))
+        .join(base36(z & 63))
+        .join(format!("c.{}.{}.dat", base36(x), base36(z)))
+}
+
+/// Format a number in base 36, the same way as Java's `Integer.toString`.
+fn base36(value: i32) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    let mut remaining = value.unsigned_abs();
+    let mut digits = Vec::new();
+    loop {
+        digits.push(DIGITS[(remaining % 36) as usize]);
+        remaining /= 36;
+        if remaining == 0 {
+            break;
+        }
+    }
+    if value < 0 {
+        digits.push(b'-');
+    }
+    digits.reverse();
+
+    String::from_utf8(digits).unwrap()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base36_matches_java() {
+        assert_eq!(base36(0), "0");
+        assert_eq!(base36(35), "z");
+        assert_eq!(base36(36), "10");
+        assert_eq!(base36(-1), "-1");
+        assert_eq!(base36(-64), "-1s");
+        assert_eq!(base36(i32::MAX), "zik0zj");
+        assert_eq!(base36(i32::MIN), "-zik0zk");
+    }
+
+    #[test]
+    fn chunk_file_paths() {
+        assert_eq!(chunk_file_path(0, 0), Path::new("0/0/c.0.0.dat"));
+        assert_eq!(chunk_file_path(-1, 64), Path::new("1r/0/c.-1.1s.dat"));
+        assert_eq!(chunk_file_path(100, -100), Path::new("10/s/c.2s.-2s.dat"));
+    }
+}